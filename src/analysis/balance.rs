@@ -0,0 +1,157 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::{Hand, column, hand_of, key_count};
+
+/// Fingers of both hands, from the left pinky to the right pinky
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    LeftThumb,
+    RightThumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    pub const COUNT: usize = 10;
+}
+
+/// Standard fingering of a key mode, one finger per column
+///
+/// Each hand takes half the columns, starting with the index finger next to
+/// the center and moving outward; hands with five or more columns start with
+/// the thumb instead, and extra columns stay on the pinky. The center column
+/// of odd key counts is played with the left thumb. For example:
+///
+/// * 4K: left middle, left index, right index, right middle
+/// * 7K: left ring, left middle, left index, left thumb, right index, right middle, right ring
+/// * 10K: every finger from the left pinky to the right pinky
+pub fn fingering(key_count: u32) -> Vec<Finger> {
+    const LEFT: [Finger; 5] = [
+        Finger::LeftThumb, Finger::LeftIndex, Finger::LeftMiddle, Finger::LeftRing, Finger::LeftPinky,
+    ];
+    const RIGHT: [Finger; 5] = [
+        Finger::RightThumb, Finger::RightIndex, Finger::RightMiddle, Finger::RightRing, Finger::RightPinky,
+    ];
+
+    let half = (key_count / 2) as usize;
+    let skip = if half >= 5 { 0 } else { 1 };
+    let outward = |hand: &[Finger; 5], i: usize| hand[(i + skip).min(4)];
+
+    let mut fingers: Vec<Finger> = (0..half).rev().map(|i| outward(&LEFT, i)).collect();
+    if key_count % 2 == 1 {
+        fingers.push(Finger::LeftThumb);
+    }
+    fingers.extend((0..half).map(|i| outward(&RIGHT, i)));
+
+    fingers
+}
+
+/// Notes played by each finger under the standard `fingering`
+///
+/// # Returns
+/// A vector of `Finger::COUNT` counts, indexed by `Finger as usize`
+pub fn finger_load(beatmap: &Beatmap) -> Vec<usize> {
+    let keys = key_count(beatmap);
+    let fingers = fingering(keys);
+    let mut load = vec![0; Finger::COUNT];
+
+    for hit_object in &beatmap.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            load[fingers[column as usize] as usize] += 1;
+        }
+    }
+
+    load
+}
+
+/// Imbalance between the hands over time
+///
+/// The center column of odd key counts belongs to neither hand and is skipped.
+///
+/// # Returns
+/// `(window_start, left_minus_right)` for back-to-back windows of `window_ms`,
+/// where `left_minus_right` is the difference of the shares of notes played by
+/// each hand, from `-1.0` (right only) to `1.0` (left only); windows without
+/// notes are omitted
+pub fn hand_load_series(beatmap: &Beatmap, window_ms: f64) -> Vec<(f64, f64)> {
+    let keys = key_count(beatmap);
+
+    let mut notes: Vec<(f64, Hand)> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| {
+            let hand = hand_of(column(hit_object, keys)?, keys)?;
+            Some((hit_object.start_time, hand))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let Some(&(first, _)) = notes.first() else {
+        return Vec::new();
+    };
+
+    let mut windows: Vec<(f64, usize, usize)> = Vec::new();
+    for (time, hand) in notes {
+        let start = first + ((time - first) / window_ms).floor() * window_ms;
+
+        if windows.last().is_none_or(|w| w.0 != start) {
+            windows.push((start, 0, 0));
+        }
+        let window = windows.last_mut().unwrap();
+        match hand {
+            Hand::Left => window.1 += 1,
+            Hand::Right => window.2 += 1,
+        }
+    }
+
+    windows.into_iter()
+        .map(|(start, left, right)| (start, (left as f64 - right as f64) / (left + right) as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    #[test]
+    fn center_column_of_7k_goes_to_the_left_thumb() {
+        let mut map = Beatmap {
+            circle_size: 7.0,
+            ..Default::default()
+        };
+        for i in 0..5 {
+            map.hit_objects.push(new_note(i as f64 * 100.0, 3, 7));
+        }
+        map.hit_objects.push(new_note(600.0, 0, 7));
+
+        let load = finger_load(&map);
+
+        assert_eq!(fingering(7)[3], Finger::LeftThumb);
+        assert_eq!(load[Finger::LeftThumb as usize], 5);
+        assert_eq!(load[Finger::LeftRing as usize], 1);
+        assert_eq!(load.iter().sum::<usize>(), 6);
+    }
+
+    #[test]
+    fn left_heavy_section_is_positive() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        let notes = [
+            (0.0, 0), (200.0, 1), (400.0, 0), (600.0, 3),
+            (1000.0, 0), (1200.0, 3), (1400.0, 1), (1600.0, 2),
+            (3000.0, 2),
+        ];
+        for (time, column) in notes {
+            map.hit_objects.push(new_note(time, column, 4));
+        }
+
+        assert_eq!(hand_load_series(&map, 1000.0), vec![(0.0, 0.5), (1000.0, 0.0), (3000.0, -1.0)]);
+    }
+}
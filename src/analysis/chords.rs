@@ -0,0 +1,95 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::{column, key_count};
+
+/// Notes hit at the same time
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chord {
+    pub time: f64,
+    /// Indices into `hit_objects`
+    pub indices: Vec<usize>,
+    pub columns: Vec<u32>,
+}
+
+impl Chord {
+    pub fn size(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+/// Groups notes starting within `tolerance_ms` of each other into chords
+///
+/// Chords are returned in time order; each chord's time is its first note.
+pub fn group_chords(beatmap: &Beatmap, tolerance_ms: f64) -> Vec<Chord> {
+    let keys = key_count(beatmap);
+
+    let mut notes: Vec<(usize, f64, u32)> = beatmap.hit_objects.iter()
+        .enumerate()
+        .filter_map(|(i, hit_object)| column(hit_object, keys).map(|c| (i, hit_object.start_time, c)))
+        .collect();
+    notes.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let mut chords: Vec<Chord> = Vec::new();
+
+    for (index, time, column) in notes {
+        match chords.last_mut() {
+            Some(chord) if time - chord.time <= tolerance_ms => {
+                chord.indices.push(index);
+                chord.columns.push(column);
+            }
+            _ => chords.push(Chord {
+                time,
+                indices: vec![index],
+                columns: vec![column],
+            }),
+        }
+    }
+
+    chords
+}
+
+/// Number of chords of each size
+///
+/// Index `i` counts the chords of exactly `i` notes, so index 1 counts single
+/// notes and index 0 is always 0. The vector ends at the largest chord size.
+pub fn chord_size_histogram(beatmap: &Beatmap, tolerance_ms: f64) -> Vec<usize> {
+    let mut histogram = vec![0];
+
+    for chord in group_chords(beatmap, tolerance_ms) {
+        if chord.size() >= histogram.len() {
+            histogram.resize(chord.size() + 1, 0);
+        }
+        histogram[chord.size()] += 1;
+    }
+
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    #[test]
+    fn chord_sizes_within_tolerance() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.hit_objects = vec![
+            new_note(0.0, 0, 4),
+            new_note(500.0, 0, 4),
+            new_note(502.0, 1, 4),
+            new_note(1000.0, 0, 4),
+            new_note(1000.0, 1, 4),
+            new_note(1001.0, 2, 4),
+            new_note(1500.0, 3, 4),
+        ];
+
+        let chords = group_chords(&map, 5.0);
+        let sizes: Vec<usize> = chords.iter().map(Chord::size).collect();
+        assert_eq!(sizes, vec![1, 2, 3, 1]);
+        assert_eq!(chords[2].columns, vec![0, 1, 2]);
+        assert_eq!(chord_size_histogram(&map, 5.0), vec![0, 2, 1, 1]);
+    }
+}
@@ -0,0 +1,179 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::key_count;
+use crate::mania::export::{GridNote, from_column_grid, to_column_grid};
+
+/// Strips a map down to its chart structure
+///
+/// The copy keeps only the key count, the red lines and the notes (time,
+/// column and hold duration) at canonical positions, sorted by time then
+/// column. Hitsounds, SV, effects and metadata are dropped, so two charts that
+/// differ only cosmetically minimize to equal beatmaps.
+pub fn minimize(beatmap: &Beatmap) -> Beatmap {
+    let mut minimized = from_column_grid(&to_column_grid(beatmap), key_count(beatmap));
+    minimized.control_points.timing_points = beatmap.control_points.timing_points.clone();
+
+    minimized
+}
+
+/// Differences between the notes of two versions of a chart
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NoteDiff {
+    /// Notes only in the new version
+    pub added: Vec<GridNote>,
+    /// Notes only in the old version
+    pub removed: Vec<GridNote>,
+    /// Notes matched across versions whose time or hold duration changed, as `(old, new)`
+    pub moved: Vec<(GridNote, GridNote)>,
+}
+
+/// Diffs the notes of `a` (old) against `b` (new)
+///
+/// Each note of `a` is matched with the nearest unmatched note of `b` in the
+/// same column within `tolerance_ms`. Matched notes that are not identical are
+/// reported as moved, the rest as removed or added.
+pub fn diff_notes(a: &Beatmap, b: &Beatmap, tolerance_ms: f64) -> NoteDiff {
+    let old = to_column_grid(a);
+    let new = to_column_grid(b);
+    let keys = key_count(a).max(key_count(b)) as usize;
+
+    let mut columns: Vec<Vec<usize>> = vec![Vec::new(); keys];
+    for (i, note) in new.iter().enumerate() {
+        columns[note.1 as usize].push(i);
+    }
+
+    let mut matched = vec![false; new.len()];
+    let mut diff = NoteDiff::default();
+
+    for note in &old {
+        let candidates = &columns[note.1 as usize];
+        let from = candidates.partition_point(|&i| new[i].0 < note.0 - tolerance_ms);
+
+        let nearest = candidates[from..].iter()
+            .take_while(|&&i| new[i].0 <= note.0 + tolerance_ms)
+            .filter(|&&i| !matched[i])
+            .min_by(|&&x, &&y| {
+                (new[x].0 - note.0).abs().partial_cmp(&(new[y].0 - note.0).abs()).unwrap()
+            });
+
+        match nearest {
+            Some(&i) => {
+                matched[i] = true;
+                if new[i] != *note {
+                    diff.moved.push((*note, new[i]));
+                }
+            }
+            None => diff.removed.push(*note),
+        }
+    }
+
+    diff.added = new.iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(note, _)| *note)
+        .collect();
+
+    diff
+}
+
+/// Stable hash of the chart structure, for caching analysis results
+///
+/// Hashes the key count and the minimized note sequence (times and hold
+/// durations rounded to 1ms, columns) with FNV-1a, so the value does not
+/// depend on metadata, hitsounds or the Rust version.
+pub fn chart_hash(beatmap: &Beatmap) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET;
+    let mut write = |value: i64| {
+        for byte in value.to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+    };
+
+    write(key_count(beatmap) as i64);
+    for (time, column, duration) in to_column_grid(beatmap) {
+        write(time.round() as i64);
+        write(column as i64);
+        write(duration.map_or(-1, |duration| duration.round() as i64));
+    }
+
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::hit_objects::hit_samples::HitSampleInfo;
+    use rosu_map::section::timing_points::{DifficultyPoint, TimingPoint};
+    use crate::mania::{new_hold, new_note};
+
+    fn chart() -> Beatmap {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.control_points.timing_points.push(TimingPoint::new(
+            0.0,
+            500.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        map.hit_objects = vec![
+            new_note(0.0, 0, 4),
+            new_note(0.0, 3, 4),
+            new_hold(250.0, 250.0, 1, 4),
+            new_note(500.0, 2, 4),
+        ];
+        map
+    }
+
+    /// `chart()` with different metadata, SV, hitsounds and object order
+    fn restyled_chart() -> Beatmap {
+        let mut map = chart();
+        map.title = "Another title".to_owned();
+        map.version = "Another difficulty".to_owned();
+        map.creator = "Someone else".to_owned();
+        map.control_points.difficulty_points.push(DifficultyPoint {
+            time: 0.0,
+            slider_velocity: 2.0,
+            generate_ticks: true,
+        });
+        map.hit_objects[2].samples.push(HitSampleInfo::new(HitSampleInfo::HIT_CLAP, None, 0, 100));
+        map.hit_objects.swap(0, 1);
+        map
+    }
+
+    #[test]
+    fn cosmetic_changes_minimize_to_the_same_map() {
+        assert_ne!(chart(), restyled_chart());
+        assert_eq!(minimize(&chart()), minimize(&restyled_chart()));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_moved_notes() {
+        let old = chart();
+        let mut new = chart();
+        new.hit_objects.remove(3);
+        new.hit_objects.push(new_note(750.0, 0, 4));
+        new.hit_objects[0].start_time = 10.0;
+
+        assert_eq!(diff_notes(&old, &new, 20.0), NoteDiff {
+            added: vec![(750.0, 0, None)],
+            removed: vec![(500.0, 2, None)],
+            moved: vec![((0.0, 0, None), (10.0, 0, None))],
+        });
+        assert_eq!(diff_notes(&old, &old, 20.0), NoteDiff::default());
+    }
+
+    #[test]
+    fn chart_hash_ignores_metadata() {
+        assert_eq!(chart_hash(&chart()), chart_hash(&restyled_chart()));
+
+        let mut moved = chart();
+        moved.hit_objects[3].start_time += 1.0;
+        assert_ne!(chart_hash(&chart()), chart_hash(&moved));
+    }
+}
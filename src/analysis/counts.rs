@@ -0,0 +1,37 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::x_of;
+
+/// Number of key presses needed to play the map
+///
+/// Circles and holds count once each: hold releases are not presses, so this
+/// is lower than the number of judgements on maps with holds.
+pub fn total_presses(beatmap: &Beatmap) -> usize {
+    beatmap.hit_objects.iter()
+        .filter(|hit_object| x_of(hit_object).is_some())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::judgement::judgement_times;
+    use crate::mania::{new_hold, new_note};
+
+    #[test]
+    fn holds_count_as_one_press_but_two_judgements() {
+        let map = Beatmap {
+            circle_size: 4.0,
+            hit_objects: vec![
+                new_note(0.0, 0, 4),
+                new_hold(100.0, 300.0, 1, 4),
+                new_note(200.0, 2, 4),
+                new_hold(500.0, 200.0, 3, 4),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(total_presses(&map), 4);
+        assert_eq!(judgement_times(&map).len(), 6);
+    }
+}
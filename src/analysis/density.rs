@@ -0,0 +1,217 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::{column, key_count, x_of};
+
+/// Sorted start times of every mania note
+pub(crate) fn note_times(beatmap: &Beatmap) -> Vec<f64> {
+    let mut times: Vec<f64> = beatmap.hit_objects.iter()
+        .filter(|hit_object| x_of(hit_object).is_some())
+        .map(|hit_object| hit_object.start_time)
+        .collect();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    times
+}
+
+/// Notes per second over a sliding window
+///
+/// # Arguments
+/// * `window_ms` - Width of the window each sample counts notes in
+/// * `step_ms` - Distance between two samples
+///
+/// # Returns
+/// `(window_start, nps)` pairs from the first note to the last one
+pub fn density_series(beatmap: &Beatmap, window_ms: f64, step_ms: f64) -> Vec<(f64, f64)> {
+    let times = note_times(beatmap);
+    let (Some(&first), Some(&last)) = (times.first(), times.last()) else {
+        return Vec::new();
+    };
+
+    sample(&times, first, last, window_ms, step_ms)
+}
+
+/// Notes per second over a sliding window, for each column
+///
+/// Every column is sampled at the same times as `density_series`, from the
+/// first note of the map to the last one, so the series line up.
+///
+/// # Returns
+/// One `(window_start, nps)` series per column
+pub fn column_density_series(beatmap: &Beatmap, window_ms: f64, step_ms: f64) -> Vec<Vec<(f64, f64)>> {
+    let keys = key_count(beatmap);
+    let all = note_times(beatmap);
+    let (Some(&first), Some(&last)) = (all.first(), all.last()) else {
+        return vec![Vec::new(); keys as usize];
+    };
+
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); keys as usize];
+    for hit_object in &beatmap.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            columns[column as usize].push(hit_object.start_time);
+        }
+    }
+
+    columns.into_iter()
+        .map(|mut times| {
+            times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sample(&times, first, last, window_ms, step_ms)
+        })
+        .collect()
+}
+
+/// Samples the density of sorted `times` every `step_ms` from `first` to `last`
+fn sample(times: &[f64], first: f64, last: f64, window_ms: f64, step_ms: f64) -> Vec<(f64, f64)> {
+    let mut series = Vec::new();
+    let mut start = first;
+    let mut lo = 0;
+    let mut hi = 0;
+
+    while start <= last {
+        while lo < times.len() && times[lo] < start {
+            lo += 1;
+        }
+        while hi < times.len() && times[hi] < start + window_ms {
+            hi += 1;
+        }
+        series.push((start, (hi - lo) as f64 * 1000.0 / window_ms));
+        start += step_ms;
+    }
+
+    series
+}
+
+/// Mean and peak notes per second over back-to-back windows of `window_ms`
+///
+/// # Returns
+/// `(mean_nps, peak_nps)`, `(0.0, 0.0)` for maps without notes
+pub fn nps_stats(beatmap: &Beatmap, window_ms: f64) -> (f64, f64) {
+    let series = density_series(beatmap, window_ms, window_ms);
+    if series.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let mean = series.iter().map(|(_, nps)| nps).sum::<f64>() / series.len() as f64;
+    let peak = series.iter().map(|&(_, nps)| nps).fold(0.0, f64::max);
+
+    (mean, peak)
+}
+
+/// Windows on each side forming the surroundings of a window in `find_dumps`
+pub const DUMP_CONTEXT_WINDOWS: usize = 8;
+
+/// Finds sudden density spikes ("dumps")
+///
+/// The map is cut in back-to-back windows of `window_ms`. A window is part of
+/// a dump when its density exceeds `spike_factor` times the average of the
+/// `DUMP_CONTEXT_WINDOWS` windows on each side, and the map's mean density,
+/// so isolated notes after silence don't count. Adjacent windows are merged.
+///
+/// # Returns
+/// `(start, end)` time ranges of each dump
+pub fn find_dumps(beatmap: &Beatmap, window_ms: f64, spike_factor: f64) -> Vec<(f64, f64)> {
+    let series = density_series(beatmap, window_ms, window_ms);
+    let (mean, _) = nps_stats(beatmap, window_ms);
+    let mut dumps: Vec<(f64, f64)> = Vec::new();
+
+    for (i, &(start, nps)) in series.iter().enumerate() {
+        let from = i.saturating_sub(DUMP_CONTEXT_WINDOWS);
+        let to = (i + DUMP_CONTEXT_WINDOWS + 1).min(series.len());
+        let around: Vec<f64> = series[from..to].iter()
+            .enumerate()
+            .filter(|&(j, _)| from + j != i)
+            .map(|(_, &(_, nps))| nps)
+            .collect();
+        if around.is_empty() {
+            continue;
+        }
+        let average = around.iter().sum::<f64>() / around.len() as f64;
+
+        if nps > spike_factor * average && nps > mean {
+            match dumps.last_mut() {
+                Some(dump) if dump.1 >= start => dump.1 = start + window_ms,
+                _ => dumps.push((start, start + window_ms)),
+            }
+        }
+    }
+
+    dumps
+}
+
+/// Mean start time of the notes, the "center of mass" of the chart
+///
+/// Compared to the midpoint between the first and last notes, an earlier
+/// centroid means a front-loaded chart and a later one a back-loaded chart.
+/// Returns 0 for maps without notes.
+pub fn density_centroid(beatmap: &Beatmap) -> f64 {
+    let times = note_times(beatmap);
+    if times.is_empty() {
+        return 0.0;
+    }
+
+    times.iter().sum::<f64>() / times.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_note};
+
+    #[test]
+    fn one_dense_column_stands_out() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects.push(new_note(0.0, 0, 4));
+        for i in 0..10 {
+            map.hit_objects.push(new_note(i as f64 * 100.0, 1, 4));
+        }
+
+        let series = column_density_series(&map, 1000.0, 500.0);
+
+        assert_eq!(series.len(), 4);
+        assert_eq!(series[0], vec![(0.0, 1.0), (500.0, 0.0)]);
+        assert_eq!(series[1], vec![(0.0, 10.0), (500.0, 5.0)]);
+        assert_eq!(series[2], vec![(0.0, 0.0), (500.0, 0.0)]);
+        assert_eq!(series[3], series[2]);
+    }
+
+    #[test]
+    fn nps_peak_exceeds_mean_on_uneven_maps() {
+        let mut map = map_4k(Vec::new());
+        for i in 0..4 {
+            map.hit_objects.push(new_note(i as f64 * 250.0, 0, 4));
+        }
+        for i in 0..12 {
+            map.hit_objects.push(new_note(1000.0 + i as f64 * 250.0 / 3.0, i % 4, 4));
+        }
+
+        assert_eq!(nps_stats(&map, 1000.0), (8.0, 12.0));
+        assert_eq!(nps_stats(&map_4k(Vec::new()), 1000.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn finds_the_one_dump() {
+        let mut map = map_4k(Vec::new());
+        for second in 0..20 {
+            let notes = if second == 10 { 20 } else { 4 };
+            for i in 0..notes {
+                let time = second as f64 * 1000.0 + i as f64 * 1000.0 / notes as f64;
+                map.hit_objects.push(new_note(time, i % 4, 4));
+            }
+        }
+
+        assert_eq!(find_dumps(&map, 1000.0, 2.0), vec![(10_000.0, 11_000.0)]);
+    }
+
+    #[test]
+    fn front_loaded_map_has_an_early_centroid() {
+        let mut map = map_4k(Vec::new());
+        for i in 0..30 {
+            map.hit_objects.push(new_note(i as f64 * 100.0, i % 4, 4));
+        }
+        for i in 0..10 {
+            map.hit_objects.push(new_note(3000.0 + i as f64 * 700.0, i % 4, 4));
+        }
+
+        let midpoint = (0.0 + 9300.0) / 2.0;
+        assert!(density_centroid(&map) < midpoint);
+        assert_eq!(density_centroid(&map_4k(Vec::new())), 0.0);
+    }
+}
@@ -0,0 +1,318 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+use rosu_map::section::timing_points::DifficultyPoint;
+
+use super::density::density_series;
+use super::judgement::{JudgeKind, judgement_times};
+use crate::mania::column::{Hand, column, hand_of, key_count};
+use crate::mania::end_time;
+
+/// Densest window played by a single hand
+///
+/// The center column of odd key counts belongs to neither hand.
+///
+/// # Returns
+/// `(window_start, nps)` of the densest `window_ms` window over both hands,
+/// `(0.0, 0.0)` for maps without notes
+pub fn hardest_burst(beatmap: &Beatmap, window_ms: f64) -> (f64, f64) {
+    let keys = key_count(beatmap);
+    let mut best = (0.0, 0.0);
+
+    for hand in [Hand::Left, Hand::Right] {
+        let mut times: Vec<f64> = beatmap.hit_objects.iter()
+            .filter(|hit_object| {
+                column(hit_object, keys).and_then(|c| hand_of(c, keys)) == Some(hand)
+            })
+            .map(|hit_object| hit_object.start_time)
+            .collect();
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut hi = 0;
+        for (lo, &start) in times.iter().enumerate() {
+            while hi < times.len() && times[hi] < start + window_ms {
+                hi += 1;
+            }
+            let nps = (hi - lo) as f64 * 1000.0 / window_ms;
+            if nps > best.1 {
+                best = (start, nps);
+            }
+        }
+    }
+
+    best
+}
+
+/// Relative difficulty estimate of a map, only meaningful for comparing maps
+///
+/// Blends the average of the densest 10% of one-second windows with the hardest
+/// single-hand burst, so both overall strain and local spikes count.
+pub fn difficulty_score(beatmap: &Beatmap) -> f64 {
+    let mut samples: Vec<f64> = density_series(beatmap, 1000.0, 250.0)
+        .into_iter()
+        .map(|(_, nps)| nps)
+        .collect();
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.sort_by(|a, b| b.partial_cmp(a).unwrap());
+
+    let top = samples.len().div_ceil(10);
+    let strain = samples[..top].iter().sum::<f64>() / top as f64;
+    let (_, burst) = hardest_burst(beatmap, 1000.0);
+
+    0.7 * strain + 0.3 * burst
+}
+
+/// Density under which a window does not count toward `stamina_score`
+pub const STAMINA_NPS_THRESHOLD: f64 = 4.0;
+
+/// Sustained density of a map, rewarding long dense passages over short spikes
+///
+/// Area under the density curve (back-to-back `window_ms` windows) above
+/// `STAMINA_NPS_THRESHOLD`, i.e. the number of notes played above that pace.
+pub fn stamina_score(beatmap: &Beatmap, window_ms: f64) -> f64 {
+    density_series(beatmap, window_ms, window_ms)
+        .into_iter()
+        .map(|(_, nps)| (nps - STAMINA_NPS_THRESHOLD).max(0.0) * window_ms / 1000.0)
+        .sum()
+}
+
+/// Weight of each SV direction reversal in `sv_difficulty`
+pub const SV_REVERSAL_WEIGHT: f64 = 0.1;
+
+/// Reading difficulty caused by SV changes
+///
+/// Standard deviation of the SV multiplier weighted by how long each one
+/// lasts over the playable span, plus `SV_REVERSAL_WEIGHT` per reversal
+/// (the scroll speeding up after slowing down, or the opposite).
+/// Constant SV scores 0.
+pub fn sv_difficulty(beatmap: &Beatmap) -> f64 {
+    let start = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .fold(f64::INFINITY, f64::min);
+    let end = beatmap.hit_objects.iter()
+        .map(end_time)
+        .fold(f64::NEG_INFINITY, f64::max);
+    if end <= start {
+        return 0.0;
+    }
+
+    let mut spans: Vec<(f64, f64)> = Vec::new();
+    let mut time = start;
+    let mut velocity = DifficultyPoint::DEFAULT_SLIDER_VELOCITY;
+
+    for point in &beatmap.control_points.difficulty_points {
+        if point.time > start {
+            spans.push((point.time.min(end) - time, velocity));
+            time = point.time.min(end);
+        }
+        velocity = point.slider_velocity;
+    }
+    spans.push((end - time, velocity));
+
+    let total: f64 = spans.iter().map(|(duration, _)| duration).sum();
+    let mean = spans.iter().map(|(duration, sv)| duration * sv).sum::<f64>() / total;
+    let variance = spans.iter()
+        .map(|(duration, sv)| duration * (sv - mean).powi(2))
+        .sum::<f64>() / total;
+
+    let deltas: Vec<f64> = spans.windows(2)
+        .map(|pair| pair[1].1 - pair[0].1)
+        .filter(|delta| delta.abs() > f64::EPSILON)
+        .collect();
+    let reversals = deltas.windows(2)
+        .filter(|pair| pair[0].signum() != pair[1].signum())
+        .count();
+
+    variance.sqrt() + SV_REVERSAL_WEIGHT * reversals as f64
+}
+
+/// Events closer than this to a release make it a coordinated release
+pub const RELEASE_COINCIDENCE_MS: f64 = 30.0;
+
+/// Hold releases that must be timed against other inputs, per second
+///
+/// A release counts when another column is pressed or released within
+/// `RELEASE_COINCIDENCE_MS` of it, which stresses finger independence.
+///
+/// # Returns
+/// `(window_start, releases_per_second)` for back-to-back windows of
+/// `window_ms` from the first input to the last one
+pub fn ln_release_difficulty(beatmap: &Beatmap, window_ms: f64) -> Vec<(f64, f64)> {
+    let events = judgement_times(beatmap);
+    let (Some(first), Some(last)) = (events.first(), events.last()) else {
+        return Vec::new();
+    };
+    let (first, last) = (first.0, last.0);
+
+    let windows = ((last - first) / window_ms).floor() as usize + 1;
+    let mut counts = vec![0usize; windows];
+
+    for (i, &(time, column, kind)) in events.iter().enumerate() {
+        if kind != JudgeKind::Release {
+            continue;
+        }
+
+        let before = events[..i].iter().rev().take_while(|e| time - e.0 <= RELEASE_COINCIDENCE_MS);
+        let after = events[i + 1..].iter().take_while(|e| e.0 - time <= RELEASE_COINCIDENCE_MS);
+
+        if before.chain(after).any(|e| e.1 != column) {
+            counts[((time - first) / window_ms) as usize] += 1;
+        }
+    }
+
+    counts.into_iter()
+        .enumerate()
+        .map(|(i, count)| (first + i as f64 * window_ms, count as f64 * 1000.0 / window_ms))
+        .collect()
+}
+
+/// Presses of one finger further apart than this are not penalized
+pub const SAME_FINGER_WINDOW_MS: f64 = 500.0;
+
+/// Extra score for a map where every press repeats the previous finger
+pub const SAME_FINGER_WEIGHT: f64 = 0.5;
+
+/// `difficulty_score` adjusted for the fingering `layout` (finger id per column)
+///
+/// Pressing a different column with the same finger as the previous press of
+/// that finger is penalized, fully when simultaneous and linearly less up to
+/// `SAME_FINGER_WINDOW_MS`. Columns missing from `layout` get a finger of
+/// their own.
+pub fn difficulty_for_layout(beatmap: &Beatmap, layout: &[u8]) -> f64 {
+    let keys = key_count(beatmap);
+    let mut presses: Vec<(f64, u32)> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| column(hit_object, keys).map(|c| (hit_object.start_time, c)))
+        .collect();
+    if presses.is_empty() {
+        return 0.0;
+    }
+    presses.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    // Last press of each finger, keyed so unmapped columns never share a finger
+    let finger_of = |c: u32| layout.get(c as usize).map_or(u8::MAX as u32 + 1 + c, |&f| f as u32);
+    let mut last: Vec<(u32, f64, u32)> = Vec::new();
+    let mut penalty = 0.0;
+
+    for &(time, c) in &presses {
+        let finger = finger_of(c);
+
+        match last.iter_mut().find(|(f, _, _)| *f == finger) {
+            Some(entry) => {
+                let gap = time - entry.1;
+                if entry.2 != c && gap < SAME_FINGER_WINDOW_MS {
+                    penalty += 1.0 - gap / SAME_FINGER_WINDOW_MS;
+                }
+                *entry = (finger, time, c);
+            }
+            None => last.push((finger, time, c)),
+        }
+    }
+
+    difficulty_score(beatmap) * (1.0 + SAME_FINGER_WEIGHT * penalty / presses.len() as f64)
+}
+
+/// How much `difficulty_score` changes when the map is played at `rate`
+///
+/// Only the note times matter to the score, so they are scaled on a copy
+/// instead of going through the rates module. Positive for speed-ups
+/// (a rate of 1.5 for DT), negative for slow-downs.
+pub fn rate_difficulty_delta(beatmap: &Beatmap, rate: f64) -> f64 {
+    let mut rated = beatmap.clone();
+
+    for hit_object in rated.hit_objects.iter_mut() {
+        hit_object.start_time /= rate;
+        if let HitObjectKind::Hold(ref mut hold) = hit_object.kind {
+            hold.duration /= rate;
+        }
+    }
+
+    difficulty_score(&rated) - difficulty_score(beatmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_hold, new_note};
+
+    #[test]
+    fn hardest_burst_finds_left_hand_burst() {
+        let mut map = map_4k(Vec::new());
+        for i in 0..10 {
+            map.hit_objects.push(new_note(i as f64 * 1000.0, 3, 4));
+        }
+        for i in 0..10 {
+            map.hit_objects.push(new_note(5000.0 + i as f64 * 50.0, i % 2, 4));
+        }
+
+        assert_eq!(hardest_burst(&map, 1000.0), (5000.0, 10.0));
+    }
+
+    /// 4K stream at `nps` notes per second lasting `duration_ms`
+    fn stream(nps: f64, duration_ms: f64) -> Beatmap {
+        let mut map = map_4k(Vec::new());
+        let spacing = 1000.0 / nps;
+        let notes = (duration_ms / spacing) as usize;
+        for i in 0..notes {
+            map.hit_objects.push(new_note(i as f64 * spacing, (i % 4) as u32, 4));
+        }
+        map
+    }
+
+    #[test]
+    fn long_moderate_map_outlasts_short_burst() {
+        let long = stream(6.0, 60_000.0);
+        let burst = stream(20.0, 2000.0);
+
+        assert!(stamina_score(&long, 1000.0) > stamina_score(&burst, 1000.0));
+    }
+
+    #[test]
+    fn oscillating_sv_is_harder_than_constant_sv() {
+        let constant = stream(4.0, 10_000.0);
+        let mut oscillating = constant.clone();
+        oscillating.control_points.difficulty_points = (0..10)
+            .map(|i| DifficultyPoint {
+                time: i as f64 * 1000.0,
+                slider_velocity: if i % 2 == 0 { 0.5 } else { 2.0 },
+                generate_ticks: true,
+            })
+            .collect();
+
+        assert_eq!(sv_difficulty(&constant), 0.0);
+        assert!(sv_difficulty(&oscillating) > 1.0);
+    }
+
+    #[test]
+    fn simultaneous_releases_count_and_lone_ones_do_not() {
+        let mut map = map_4k(Vec::new());
+        for i in 0..4 {
+            let time = i as f64 * 250.0;
+            map.hit_objects.push(new_hold(time, 200.0, 0, 4));
+            map.hit_objects.push(new_hold(time, 200.0, 1, 4));
+        }
+        map.hit_objects.push(new_hold(1500.0, 200.0, 2, 4));
+
+        assert_eq!(ln_release_difficulty(&map, 1000.0), vec![(0.0, 8.0), (1000.0, 0.0)]);
+    }
+
+    #[test]
+    fn shared_fingers_make_the_layout_harder() {
+        let map = stream(8.0, 10_000.0);
+
+        let separate = difficulty_for_layout(&map, &[0, 1, 2, 3]);
+        let shared = difficulty_for_layout(&map, &[0, 0, 1, 1]);
+
+        assert_eq!(separate, difficulty_score(&map));
+        assert!(shared > separate);
+    }
+
+    #[test]
+    fn speeding_up_makes_the_map_harder() {
+        let map = stream(6.0, 10_000.0);
+
+        assert!(rate_difficulty_delta(&map, 1.5) > 0.0);
+        assert!(rate_difficulty_delta(&map, 0.75) < 0.0);
+        assert_eq!(rate_difficulty_delta(&map, 1.0), 0.0);
+    }
+}
@@ -0,0 +1,62 @@
+use rosu_map::Beatmap;
+use serde_json::json;
+
+use super::density::density_series;
+use super::patterns::classify_pattern;
+use super::summary::summary;
+use crate::timing::bpm_segments;
+
+/// Window of the exported density series
+pub const DENSITY_WINDOW_MS: f64 = 1000.0;
+/// Step of the exported density series
+pub const DENSITY_STEP_MS: f64 = 500.0;
+
+/// Serializes the analysis of a map into a single JSON object
+///
+/// The object holds `summary`, `pattern`, `bpm_segments` and `density`
+/// (`{ "time", "nps" }` samples over a 1s window every 500ms).
+pub fn analysis_json(beatmap: &Beatmap) -> String {
+    let summary = summary(beatmap);
+    let end = beatmap.hit_objects.iter()
+        .map(crate::mania::end_time)
+        .fold(0.0, f64::max);
+
+    let density: Vec<_> = density_series(beatmap, DENSITY_WINDOW_MS, DENSITY_STEP_MS)
+        .into_iter()
+        .map(|(time, nps)| json!({ "time": time, "nps": nps }))
+        .collect();
+
+    json!({
+        "summary": summary,
+        "pattern": classify_pattern(beatmap),
+        "bpm_segments": bpm_segments(beatmap, end),
+        "density": density,
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+    use crate::mania::new_note;
+
+    #[test]
+    fn json_holds_every_section() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.control_points.timing_points.push(TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+        for i in 0..16 {
+            map.hit_objects.push(new_note(i as f64 * 125.0, i % 4, 4));
+        }
+
+        let value: serde_json::Value = serde_json::from_str(&analysis_json(&map)).unwrap();
+
+        assert_eq!(value["summary"]["notes"], 16);
+        assert_eq!(value["pattern"], "stream");
+        assert_eq!(value["bpm_segments"][0]["bpm"], 120.0);
+        assert!(!value["density"].as_array().unwrap().is_empty());
+    }
+}
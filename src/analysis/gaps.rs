@@ -0,0 +1,91 @@
+use rosu_map::Beatmap;
+
+use crate::mania::end_time;
+
+/// Empty stretches between notes, as `(start, end)` sorted by time
+///
+/// A gap starts when every note so far has ended (holds included) and ends at
+/// the next note.
+pub fn note_gaps(beatmap: &Beatmap) -> Vec<(f64, f64)> {
+    let mut notes: Vec<(f64, f64)> = beatmap.hit_objects.iter()
+        .map(|hit_object| (hit_object.start_time, end_time(hit_object)))
+        .collect();
+    notes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let mut gaps = Vec::new();
+    let mut played_until = f64::NEG_INFINITY;
+
+    for (start, end) in notes {
+        if played_until.is_finite() && start > played_until {
+            gaps.push((played_until, start));
+        }
+        played_until = played_until.max(end);
+    }
+
+    gaps
+}
+
+/// Suggests breaks so no stretch of continuous play exceeds `max_segment_ms`
+///
+/// Each break is the longest gap between notes within `max_segment_ms` of the
+/// previous break (or of the first note), so breaks land where the chart
+/// already pauses rather than mid-stream. If no gap fits, the first gap after
+/// the limit is used.
+///
+/// # Returns
+/// `(start, end)` of each suggested break, sorted by time
+pub fn suggest_breaks(beatmap: &Beatmap, max_segment_ms: f64) -> Vec<(f64, f64)> {
+    let gaps = note_gaps(beatmap);
+    let Some(first) = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+    else {
+        return Vec::new();
+    };
+    let last = beatmap.hit_objects.iter().map(end_time).fold(first, f64::max);
+
+    let mut breaks = Vec::new();
+    let mut segment_start = first;
+
+    while last - segment_start > max_segment_ms {
+        let limit = segment_start + max_segment_ms;
+        let after: Vec<&(f64, f64)> = gaps.iter().filter(|gap| gap.0 > segment_start).collect();
+
+        let within = after.iter()
+            .filter(|gap| gap.0 <= limit)
+            .max_by(|a, b| (a.1 - a.0).partial_cmp(&(b.1 - b.0)).unwrap());
+
+        let Some(&&gap) = within.or(after.first()) else {
+            break;
+        };
+
+        breaks.push(gap);
+        segment_start = gap.1;
+    }
+
+    breaks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{new_hold, new_note};
+
+    #[test]
+    fn break_lands_on_the_long_gap() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        for i in 0..29 {
+            map.hit_objects.push(new_note(i as f64 * 100.0, i % 4, 4));
+        }
+        map.hit_objects.push(new_hold(2900.0, 300.0, 1, 4));
+        for i in 0..30 {
+            map.hit_objects.push(new_note(5000.0 + i as f64 * 100.0, i % 4, 4));
+        }
+
+        assert_eq!(suggest_breaks(&map, 4000.0), vec![(3200.0, 5000.0)]);
+        assert!(suggest_breaks(&map, 10_000.0).is_empty());
+    }
+}
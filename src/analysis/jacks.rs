@@ -0,0 +1,122 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::{column, key_count};
+
+/// Consecutive notes in one column
+#[derive(Clone, Debug, PartialEq)]
+pub struct JackRun {
+    pub column: u32,
+    /// Time of the first note
+    pub start: f64,
+    /// Time of the last note
+    pub end: f64,
+    /// Number of notes in the run (at least 2)
+    pub notes: usize,
+}
+
+/// Finds runs of notes in the same column each at most `max_gap_ms` after the previous one
+///
+/// Runs are sorted by start time.
+pub fn find_jacks(beatmap: &Beatmap, max_gap_ms: f64) -> Vec<JackRun> {
+    let keys = key_count(beatmap);
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); keys as usize];
+
+    for hit_object in &beatmap.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            columns[column as usize].push(hit_object.start_time);
+        }
+    }
+
+    let mut runs = Vec::new();
+
+    for (column, times) in columns.iter_mut().enumerate() {
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let mut i = 0;
+        while i < times.len() {
+            let mut j = i;
+            while j + 1 < times.len() && times[j + 1] - times[j] <= max_gap_ms {
+                j += 1;
+            }
+            if j > i {
+                runs.push(JackRun {
+                    column: column as u32,
+                    start: times[i],
+                    end: times[j],
+                    notes: j - i + 1,
+                });
+            }
+            i = j + 1;
+        }
+    }
+
+    runs.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+    runs
+}
+
+/// Jack run with the most notes, the earliest one on ties
+pub fn longest_jack(beatmap: &Beatmap, max_gap_ms: f64) -> Option<JackRun> {
+    find_jacks(beatmap, max_gap_ms)
+        .into_iter()
+        .reduce(|best, run| if run.notes > best.notes { run } else { best })
+}
+
+/// Number of jack runs of each length, as `(notes, count)` sorted by length
+///
+/// Length 2 is a minijack, 3 a triple and so on.
+pub fn jack_histogram(beatmap: &Beatmap, max_gap_ms: f64) -> Vec<(u32, usize)> {
+    let mut histogram: Vec<(u32, usize)> = Vec::new();
+
+    for run in find_jacks(beatmap, max_gap_ms) {
+        let length = run.notes as u32;
+        match histogram.iter_mut().find(|(l, _)| *l == length) {
+            Some((_, count)) => *count += 1,
+            None => histogram.push((length, 1)),
+        }
+    }
+
+    histogram.sort_by_key(|&(length, _)| length);
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_note};
+
+    /// Adds `notes` notes to `column`, 100ms apart from `start`
+    fn push_jack(map: &mut Beatmap, column: u32, start: f64, notes: usize) {
+        for i in 0..notes {
+            map.hit_objects.push(new_note(start + i as f64 * 100.0, column, 4));
+        }
+    }
+
+    #[test]
+    fn longest_jack_is_the_five_note_run() {
+        let mut map = map_4k(Vec::new());
+        push_jack(&mut map, 0, 0.0, 3);
+        push_jack(&mut map, 2, 1000.0, 5);
+
+        let jacks = find_jacks(&map, 150.0);
+        assert_eq!(jacks.len(), 2);
+        assert_eq!(jacks[0].notes, 3);
+
+        assert_eq!(
+            longest_jack(&map, 150.0),
+            Some(JackRun { column: 2, start: 1000.0, end: 1400.0, notes: 5 })
+        );
+    }
+
+    #[test]
+    fn histogram_counts_runs_by_length() {
+        let mut map = map_4k(Vec::new());
+        push_jack(&mut map, 0, 0.0, 2);
+        push_jack(&mut map, 1, 0.0, 2);
+        push_jack(&mut map, 2, 0.0, 3);
+        push_jack(&mut map, 3, 0.0, 1);
+        push_jack(&mut map, 0, 5000.0, 4);
+        push_jack(&mut map, 1, 5000.0, 2);
+
+        assert_eq!(jack_histogram(&map, 150.0), vec![(2, 3), (3, 1), (4, 1)]);
+    }
+}
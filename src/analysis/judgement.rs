@@ -0,0 +1,117 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use crate::mania::column::{column, key_count};
+use crate::mania::end_time;
+
+/// Input event an autoplay bot produces
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JudgeKind {
+    Press,
+    Release,
+}
+
+/// Press and release events of a perfect play, as `(time, column, kind)`
+///
+/// Circles produce a single press, holds a press and a release.
+/// Events are sorted by time; at equal times releases come first so a column
+/// is freed before it is pressed again.
+pub fn judgement_times(beatmap: &Beatmap) -> Vec<(f64, u32, JudgeKind)> {
+    let keys = key_count(beatmap);
+    let mut events = Vec::new();
+
+    for hit_object in &beatmap.hit_objects {
+        let Some(column) = column(hit_object, keys) else {
+            continue;
+        };
+
+        events.push((hit_object.start_time, column, JudgeKind::Press));
+        if let HitObjectKind::Hold(ref hold) = hit_object.kind {
+            events.push((hit_object.start_time + hold.duration, column, JudgeKind::Release));
+        }
+    }
+
+    events.sort_by(|a, b| {
+        a.0.partial_cmp(&b.0)
+            .unwrap()
+            .then_with(|| (a.2 == JudgeKind::Press).cmp(&(b.2 == JudgeKind::Press)))
+            .then_with(|| a.1.cmp(&b.1))
+    });
+
+    events
+}
+
+/// Columns held down by a perfect play, sampled every `resolution_ms` from time 0
+///
+/// Bit `c` of step `i` is set when column `c` is pressed during
+/// `[i * resolution_ms, (i + 1) * resolution_ms)`: circles in the step of
+/// their press, holds in every step from the press to the release (holds
+/// with a negative duration only in the step of their press). Only the
+/// first 8 columns fit in the mask; notes before 0 are skipped.
+///
+/// # Returns
+/// One mask per step, up to the step of the last release
+pub fn keypress_timeline(beatmap: &Beatmap, resolution_ms: f64) -> Vec<u8> {
+    let keys = key_count(beatmap);
+    let mut timeline: Vec<u8> = Vec::new();
+
+    for hit_object in &beatmap.hit_objects {
+        let Some(column) = column(hit_object, keys).filter(|&c| c < u8::BITS) else {
+            continue;
+        };
+        if hit_object.start_time < 0.0 {
+            continue;
+        }
+
+        let first = (hit_object.start_time / resolution_ms) as usize;
+        let last = ((end_time(hit_object) / resolution_ms) as usize).max(first);
+        if timeline.len() <= last {
+            timeline.resize(last + 1, 0);
+        }
+
+        for step in &mut timeline[first..=last] {
+            *step |= 1 << column;
+        }
+    }
+
+    timeline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_hold, new_note};
+
+    #[test]
+    fn circle_and_hold_give_three_sorted_events() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects.push(new_hold(100.0, 400.0, 2, 4));
+        map.hit_objects.push(new_note(300.0, 0, 4));
+
+        assert_eq!(
+            judgement_times(&map),
+            vec![
+                (100.0, 2, JudgeKind::Press),
+                (300.0, 0, JudgeKind::Press),
+                (500.0, 2, JudgeKind::Release),
+            ]
+        );
+    }
+
+    #[test]
+    fn hold_sets_every_step_it_covers() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects.push(new_hold(100.0, 250.0, 2, 4));
+        map.hit_objects.push(new_note(200.0, 0, 4));
+
+        assert_eq!(keypress_timeline(&map, 100.0), vec![0, 4, 5, 4]);
+    }
+
+    #[test]
+    fn negative_duration_hold_only_sets_its_press() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects.push(new_hold(300.0, -200.0, 1, 4));
+
+        assert_eq!(keypress_timeline(&map, 100.0), vec![0, 0, 0, 2]);
+    }
+}
@@ -0,0 +1,14 @@
+pub mod balance;
+pub mod chords;
+pub mod compare;
+pub mod counts;
+pub mod density;
+pub mod difficulty;
+pub mod gaps;
+pub mod jacks;
+pub mod judgement;
+pub mod patterns;
+pub mod summary;
+
+#[cfg(feature = "serde")]
+pub mod export;
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+
+use rosu_map::Beatmap;
+
+use super::chords::group_chords;
+use super::density::{density_series, note_times};
+use crate::mania::column::{column, hand_of, key_count};
+
+/// Tolerance used to group notes into chords when classifying patterns
+pub const CHORD_TOLERANCE_MS: f64 = 2.0;
+
+/// Broad pattern family a chart belongs to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(rename_all = "lowercase"))]
+pub enum PatternKind {
+    Stream,
+    Jumpstream,
+    Handstream,
+    Jack,
+    Chordjack,
+}
+
+/// Classifies the dominant pattern of a map
+///
+/// Looks at the share of chords repeating a column of the previous chord
+/// (jacks) and the share of jumps (2 notes) and hands (3+ notes).
+/// Returns `None` for maps without notes.
+pub fn classify_pattern(beatmap: &Beatmap) -> Option<PatternKind> {
+    let chords = group_chords(beatmap, CHORD_TOLERANCE_MS);
+    if chords.is_empty() {
+        return None;
+    }
+
+    let total = chords.len() as f64;
+    let jumps = chords.iter().filter(|chord| chord.size() == 2).count() as f64;
+    let hands = chords.iter().filter(|chord| chord.size() >= 3).count() as f64;
+    let jacks = chords.windows(2)
+        .filter(|pair| pair[1].columns.iter().any(|c| pair[0].columns.contains(c)))
+        .count() as f64;
+
+    let kind = if jacks / total > 0.5 {
+        if (jumps + hands) / total > 0.5 {
+            PatternKind::Chordjack
+        } else {
+            PatternKind::Jack
+        }
+    } else if hands / total > 0.2 {
+        PatternKind::Handstream
+    } else if jumps / total > 0.2 {
+        PatternKind::Jumpstream
+    } else {
+        PatternKind::Stream
+    };
+
+    Some(kind)
+}
+
+/// Share of consecutive notes that change hands, per window
+///
+/// Notes are taken in time order; the center column of odd key counts is
+/// skipped. High rates point to jumpstream/handstream, low rates to jacks and
+/// one-handed patterns.
+///
+/// # Returns
+/// `(window_start, switch_rate)` pairs for back-to-back windows of `window_ms`,
+/// where `switch_rate` is in `0.0..=1.0`; windows without notes are omitted
+pub fn column_switch_rate(beatmap: &Beatmap, window_ms: f64) -> Vec<(f64, f64)> {
+    let keys = key_count(beatmap);
+
+    let mut notes: Vec<(f64, _)> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| {
+            let hand = hand_of(column(hit_object, keys)?, keys)?;
+            Some((hit_object.start_time, hand))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let Some(&(first, _)) = notes.first() else {
+        return Vec::new();
+    };
+
+    let mut windows: Vec<(f64, usize, usize)> = Vec::new();
+    for pair in notes.windows(2) {
+        let (time, hand) = pair[1];
+        let start = first + ((time - first) / window_ms).floor() * window_ms;
+
+        if windows.last().is_none_or(|w| w.0 != start) {
+            windows.push((start, 0, 0));
+        }
+        let window = windows.last_mut().unwrap();
+        window.1 += 1;
+        if hand != pair[0].1 {
+            window.2 += 1;
+        }
+    }
+
+    windows.into_iter()
+        .map(|(start, pairs, switches)| (start, switches as f64 / pairs as f64))
+        .collect()
+}
+
+/// Width of the back-to-back windows compared by `burst_boundaries`
+pub const BURST_WINDOW_MS: f64 = 500.0;
+
+/// Times at which bursts begin
+///
+/// A burst begins in a `BURST_WINDOW_MS` window holding more than `factor`
+/// times the notes of the window before it (any note counts after an empty
+/// window). The reported time is the first note of that window.
+pub fn burst_boundaries(beatmap: &Beatmap, factor: f64) -> Vec<f64> {
+    let times = note_times(beatmap);
+    let series = density_series(beatmap, BURST_WINDOW_MS, BURST_WINDOW_MS);
+
+    series.windows(2)
+        .filter(|pair| pair[1].1 > 0.0 && pair[1].1 > factor * pair[0].1)
+        .filter_map(|pair| times.get(times.partition_point(|&time| time < pair[1].0)).copied())
+        .collect()
+}
+
+/// How varied the column patterns of a map are, from `0.0` to `1.0`
+///
+/// The map is cut into back-to-back `window_ms` windows, each described by the
+/// columns of its chords in order. Returns the Shannon entropy of those
+/// descriptions divided by its maximum, so a map repeating one pattern scores
+/// `0.0` and one never repeating a window scores `1.0`. Maps with fewer than
+/// two non-empty windows score `0.0`.
+pub fn pattern_entropy(beatmap: &Beatmap, window_ms: f64) -> f64 {
+    let chords = group_chords(beatmap, CHORD_TOLERANCE_MS);
+    let Some(first) = chords.first().map(|chord| chord.time) else {
+        return 0.0;
+    };
+
+    let mut windows: Vec<(i64, Vec<Vec<u32>>)> = Vec::new();
+    for chord in &chords {
+        let window = ((chord.time - first) / window_ms).floor() as i64;
+        if windows.last().is_none_or(|w| w.0 != window) {
+            windows.push((window, Vec::new()));
+        }
+
+        let mut columns = chord.columns.clone();
+        columns.sort_unstable();
+        windows.last_mut().unwrap().1.push(columns);
+    }
+
+    if windows.len() < 2 {
+        return 0.0;
+    }
+
+    let mut counts: HashMap<Vec<Vec<u32>>, usize> = HashMap::new();
+    for (_, pattern) in &windows {
+        *counts.entry(pattern.clone()).or_default() += 1;
+    }
+
+    let total = windows.len() as f64;
+    let entropy: f64 = counts.values()
+        .map(|&count| {
+            let p = count as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum();
+
+    entropy / total.log2()
+}
+
+/// Fewest notes a column needs in one window to count as an anchor
+pub const ANCHOR_MIN_NOTES: usize = 4;
+/// Smallest share of a window's notes the anchor column must hold
+pub const ANCHOR_SHARE: f64 = 0.4;
+
+/// Sections where one column keeps being hit while the others vary
+///
+/// The map is cut into back-to-back `window_ms` windows. A column anchors a
+/// window when it has at least `ANCHOR_MIN_NOTES` notes making up
+/// `ANCHOR_SHARE` of the window, and at least two other columns are played.
+///
+/// # Returns
+/// `(start, column)` of each anchor section, where consecutive windows
+/// anchored on the same column form one section
+pub fn find_anchors(beatmap: &Beatmap, window_ms: f64) -> Vec<(f64, u32)> {
+    let keys = key_count(beatmap);
+
+    let mut notes: Vec<(f64, u32)> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| column(hit_object, keys).map(|c| (hit_object.start_time, c)))
+        .collect();
+    notes.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    let Some(&(first, _)) = notes.first() else {
+        return Vec::new();
+    };
+
+    let mut windows: Vec<(i64, Vec<usize>)> = Vec::new();
+    for &(time, c) in &notes {
+        let window = ((time - first) / window_ms).floor() as i64;
+        if windows.last().is_none_or(|w| w.0 != window) {
+            windows.push((window, vec![0; keys as usize]));
+        }
+        windows.last_mut().unwrap().1[c as usize] += 1;
+    }
+
+    let mut anchors = Vec::new();
+    let mut previous: Option<(i64, u32)> = None;
+
+    for (window, counts) in windows {
+        let total: usize = counts.iter().sum();
+        let (anchor, &most) = counts.iter().enumerate().max_by_key(|&(_, count)| count).unwrap();
+        let others = counts.iter().filter(|&&count| count > 0).count() - 1;
+
+        if most < ANCHOR_MIN_NOTES || (most as f64) < ANCHOR_SHARE * total as f64 || others < 2 {
+            continue;
+        }
+
+        let anchor = anchor as u32;
+        if previous != Some((window - 1, anchor)) {
+            anchors.push((first + window as f64 * window_ms, anchor));
+        }
+        previous = Some((window, anchor));
+    }
+
+    anchors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    fn map_4k(columns: impl Iterator<Item = u32>, spacing_ms: f64) -> Beatmap {
+        Beatmap {
+            circle_size: 4.0,
+            hit_objects: columns.enumerate().map(|(i, c)| new_note(i as f64 * spacing_ms, c, 4)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn jacks_switch_less_than_trills() {
+        let jack = map_4k(std::iter::repeat_n(0, 16), 100.0);
+        let trill = map_4k((0..16).map(|i| if i % 2 == 0 { 0 } else { 3 }), 100.0);
+
+        let jack_rates = column_switch_rate(&jack, 1000.0);
+        let trill_rates = column_switch_rate(&trill, 1000.0);
+
+        assert!(jack_rates.iter().all(|&(_, rate)| rate == 0.0));
+        assert!(trill_rates.iter().all(|&(_, rate)| rate == 1.0));
+    }
+
+    #[test]
+    fn burst_starts_where_the_stream_gets_dense() {
+        let mut map = map_4k((0..8).map(|i| i % 4), 250.0);
+        for i in 0..20 {
+            map.hit_objects.push(new_note(2000.0 + i as f64 * 50.0, i % 4, 4));
+        }
+
+        assert_eq!(burst_boundaries(&map, 2.0), vec![2000.0]);
+    }
+
+    #[test]
+    fn jacks_are_less_varied_than_mixed_patterns() {
+        let jack = map_4k(std::iter::repeat_n(0, 32), 125.0);
+        let varied = map_4k((0..32u32).map(|i| (i * i + i / 3) % 4), 125.0);
+
+        assert_eq!(pattern_entropy(&jack, 500.0), 0.0);
+        assert!(pattern_entropy(&varied, 500.0) > 0.5);
+    }
+
+    #[test]
+    fn finds_each_anchor_section() {
+        let others = [0, 2, 3];
+        let anchored = (0..20).map(|i| if i % 2 == 0 { 1u32 } else { others[i / 2 % 3] });
+        let roll = (0..20).map(|i| (i % 4) as u32);
+        let others = [0, 1, 3];
+        let anchored_again = (0..10).map(|i| if i % 2 == 0 { 2u32 } else { others[i / 2 % 3] });
+
+        let map = map_4k(anchored.chain(roll).chain(anchored_again), 100.0);
+
+        assert_eq!(find_anchors(&map, 1000.0), vec![(0.0, 1), (4000.0, 2)]);
+    }
+}
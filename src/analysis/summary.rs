@@ -0,0 +1,116 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use super::density::note_times;
+use crate::mania::column::key_count;
+use crate::mania::end_time;
+
+/// Headline numbers of a map
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MapSummary {
+    pub key_count: u32,
+    pub notes: usize,
+    pub holds: usize,
+    /// Share of holds among all notes
+    pub ln_ratio: f64,
+    /// Time from the first note to the end of the last one
+    pub duration_ms: f64,
+    /// Average notes per second over `duration_ms`
+    pub nps: f64,
+}
+
+/// Computes the summary of a map
+pub fn summary(beatmap: &Beatmap) -> MapSummary {
+    let notes = beatmap.hit_objects.iter()
+        .filter(|hit_object| matches!(hit_object.kind, HitObjectKind::Circle(_)))
+        .count();
+    let holds = beatmap.hit_objects.iter()
+        .filter(|hit_object| matches!(hit_object.kind, HitObjectKind::Hold(_)))
+        .count();
+    let total = notes + holds;
+
+    let start = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .fold(f64::INFINITY, f64::min);
+    let end = beatmap.hit_objects.iter()
+        .map(end_time)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let duration_ms = if total == 0 { 0.0 } else { end - start };
+
+    MapSummary {
+        key_count: key_count(beatmap),
+        notes,
+        holds,
+        ln_ratio: if total == 0 { 0.0 } else { holds as f64 / total as f64 },
+        duration_ms,
+        nps: if duration_ms > 0.0 { total as f64 * 1000.0 / duration_ms } else { 0.0 },
+    }
+}
+
+/// Index and end time of the object ending last, holds included
+///
+/// Unlike the marathon duration, this tells which object defines the end of
+/// the map. Returns `None` for maps without objects.
+pub fn last_object_end(beatmap: &Beatmap) -> Option<(usize, f64)> {
+    beatmap.hit_objects.iter()
+        .enumerate()
+        .map(|(i, hit_object)| (i, end_time(hit_object)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+}
+
+/// Start of the densest `clip_ms` window, a good highlight for `preview_time`
+///
+/// Windows start on a note; the earliest one wins ties. Returns 0 for maps
+/// without notes.
+pub fn suggest_preview(beatmap: &Beatmap, clip_ms: f64) -> f64 {
+    let times = note_times(beatmap);
+    let mut best = (0.0, 0);
+    let mut hi = 0;
+
+    for (lo, &start) in times.iter().enumerate() {
+        while hi < times.len() && times[hi] < start + clip_ms {
+            hi += 1;
+        }
+        if hi - lo > best.1 {
+            best = (start, hi - lo);
+        }
+    }
+
+    best.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{new_hold, new_note};
+
+    #[test]
+    fn last_object_end_picks_a_hold_ending_after_the_final_circle() {
+        let map = Beatmap {
+            circle_size: 4.0,
+            hit_objects: vec![new_hold(1000.0, 2000.0, 0, 4), new_note(2500.0, 1, 4)],
+            ..Default::default()
+        };
+
+        assert_eq!(last_object_end(&map), Some((0, 3000.0)));
+        assert_eq!(summary(&map).duration_ms, 2000.0);
+    }
+
+    #[test]
+    fn preview_starts_in_the_densest_section() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        for i in 0..60 {
+            let spacing = if (20..40).contains(&i) { 100.0 } else { 500.0 };
+            let time = map.hit_objects.last().map_or(0.0, |last| last.start_time + spacing);
+            map.hit_objects.push(new_note(time, i % 4, 4));
+        }
+
+        // Notes 19 to 39 are 100ms apart, from 9500ms to 11500ms
+        assert_eq!(suggest_preview(&map, 2000.0), 9500.0);
+        assert_eq!(suggest_preview(&Beatmap::default(), 2000.0), 0.0);
+    }
+}
@@ -0,0 +1,230 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use rosu_map::Beatmap;
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::TimingPoint;
+
+use crate::mania::column::{column, key_count};
+use crate::mania::{end_time, new_hold, new_note};
+
+/// Rows of a pattern per beat
+pub const ROWS_PER_BEAT: u32 = 4;
+
+/// Error when parsing a text pattern
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    /// A row does not have one character per lane
+    RowLength { row: usize, expected: usize, found: usize },
+    /// A character other than `x`, `-`, `H` or `|`
+    UnexpectedChar { row: usize, lane: usize, found: char },
+    /// A `|` not following an `H` or another `|`
+    DanglingSustain { row: usize, lane: usize },
+    /// An `H` not followed by any `|`
+    EmptyHold { row: usize, lane: usize },
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::RowLength { row, expected, found } => {
+                write!(f, "row {} has {} lanes, expected {}", row, found, expected)
+            }
+            Self::UnexpectedChar { row, lane, found } => {
+                write!(f, "unexpected '{}' at row {}, lane {}", found, row, lane)
+            }
+            Self::DanglingSustain { row, lane } => {
+                write!(f, "'|' without a hold at row {}, lane {}", row, lane)
+            }
+            Self::EmptyHold { row, lane } => {
+                write!(f, "hold without a body at row {}, lane {}", row, lane)
+            }
+        }
+    }
+}
+
+impl Error for PatternError {}
+
+/// Builds a map from a text grid
+///
+/// Each line is a 1/4 beat and each character a lane: `x` is a note, `-` is
+/// empty, `H` starts a hold and `|` sustains it, the hold ending on its last
+/// `|` row. Blank lines are ignored. For example, a jump followed by a hold:
+///
+/// ```text
+/// x--x
+/// -H--
+/// -|--
+/// ```
+///
+/// # Arguments
+/// * `text` - The pattern grid
+/// * `bpm` - Tempo of the single red line, placed at 0ms
+/// * `key_count` - Number of lanes every row must have
+pub fn from_pattern_text(text: &str, bpm: f64, key_count: u32) -> Result<Beatmap, PatternError> {
+    let beat_len = 60_000.0 / bpm;
+    let step = beat_len / ROWS_PER_BEAT as f64;
+    let lanes = key_count as usize;
+
+    let rows: Vec<Vec<char>> = text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().collect())
+        .collect();
+
+    let mut map = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: key_count as f32,
+        ..Default::default()
+    };
+    map.control_points.timing_points.push(TimingPoint::new(
+        0.0,
+        beat_len,
+        false,
+        TimingPoint::DEFAULT_TIME_SIGNATURE,
+    ));
+
+    // Row of the `H` of the hold currently open in each lane, with its last `|` row
+    let mut open: Vec<Option<(usize, Option<usize>)>> = vec![None; lanes];
+
+    for (row, chars) in rows.iter().enumerate() {
+        if chars.len() != lanes {
+            return Err(PatternError::RowLength { row, expected: lanes, found: chars.len() });
+        }
+
+        for (lane, &c) in chars.iter().enumerate() {
+            if c != '|' && let Some(hold) = open[lane].take() {
+                push_hold(&mut map, hold, lane, step, key_count)?;
+            }
+
+            match c {
+                '-' => {}
+                'x' => map.hit_objects.push(new_note(row as f64 * step, lane as u32, key_count)),
+                'H' => open[lane] = Some((row, None)),
+                '|' => match open[lane].as_mut() {
+                    Some((_, end)) => *end = Some(row),
+                    None => return Err(PatternError::DanglingSustain { row, lane }),
+                },
+                found => return Err(PatternError::UnexpectedChar { row, lane, found }),
+            }
+        }
+    }
+
+    for (lane, hold) in open.into_iter().enumerate() {
+        if let Some(hold) = hold {
+            push_hold(&mut map, hold, lane, step, key_count)?;
+        }
+    }
+
+    map.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+    Ok(map)
+}
+
+fn push_hold(
+    map: &mut Beatmap,
+    (start, end): (usize, Option<usize>),
+    lane: usize,
+    step: f64,
+    key_count: u32,
+) -> Result<(), PatternError> {
+    let end = end.ok_or(PatternError::EmptyHold { row: start, lane })?;
+    let duration = (end - start) as f64 * step;
+    map.hit_objects.push(new_hold(start as f64 * step, duration, lane as u32, key_count));
+    Ok(())
+}
+
+/// Writes the notes of a map as a text grid, the inverse of `from_pattern_text`
+///
+/// Notes are snapped to the 1/`division` grid of the first red line (1000ms
+/// beats without one), one row per grid line starting at the red line.
+/// Notes sharing a cell are merged. Use `division = ROWS_PER_BEAT` for a grid
+/// `from_pattern_text` reads back at the same BPM.
+pub fn to_pattern_text(beatmap: &Beatmap, division: u32) -> String {
+    let keys = key_count(beatmap);
+    let (origin, beat_len) = beatmap.control_points.timing_points.first()
+        .map_or((0.0, 1000.0), |point| (point.time, point.beat_len));
+    let step = beat_len / division as f64;
+    let row_of = |time: f64| ((time - origin) / step).round() as i64;
+
+    let notes: Vec<(i64, i64, u32)> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| {
+            let column = column(hit_object, keys)?;
+            Some((row_of(hit_object.start_time), row_of(end_time(hit_object)), column))
+        })
+        .collect();
+
+    let Some(last) = notes.iter().map(|&(start, end, _)| start.max(end)).max() else {
+        return String::new();
+    };
+    let first = notes.iter().map(|&(start, _, _)| start).min().unwrap_or(0).min(0);
+
+    let mut grid = vec![vec!['-'; keys as usize]; (last - first + 1) as usize];
+    for (start, end, column) in notes {
+        let lane = column as usize;
+        if end > start {
+            grid[(start - first) as usize][lane] = 'H';
+            for row in start + 1..=end {
+                grid[(row - first) as usize][lane] = '|';
+            }
+        } else {
+            grid[(start - first) as usize][lane] = 'x';
+        }
+    }
+
+    grid.into_iter()
+        .map(|row| row.into_iter().collect::<String>() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_notes_and_holds() {
+        let map = from_pattern_text("x--x\n-H--\n-|--\n", 150.0, 4).unwrap();
+
+        let objects: Vec<(f64, f64, Option<u32>)> = map.hit_objects.iter()
+            .map(|hit_object| (hit_object.start_time, end_time(hit_object), column(hit_object, 4)))
+            .collect();
+        assert_eq!(objects, vec![
+            (0.0, 0.0, Some(0)),
+            (0.0, 0.0, Some(3)),
+            (100.0, 200.0, Some(1)),
+        ]);
+    }
+
+    #[test]
+    fn reports_each_pattern_error() {
+        assert_eq!(
+            from_pattern_text("x--\n", 120.0, 4).unwrap_err(),
+            PatternError::RowLength { row: 0, expected: 4, found: 3 }
+        );
+        assert_eq!(
+            from_pattern_text("x-o-\n", 120.0, 4).unwrap_err(),
+            PatternError::UnexpectedChar { row: 0, lane: 2, found: 'o' }
+        );
+        assert_eq!(
+            from_pattern_text("x---\n-|--\n", 120.0, 4).unwrap_err(),
+            PatternError::DanglingSustain { row: 1, lane: 1 }
+        );
+        assert_eq!(
+            from_pattern_text("H---\nx---\n", 120.0, 4).unwrap_err(),
+            PatternError::EmptyHold { row: 0, lane: 0 }
+        );
+    }
+
+    #[test]
+    fn quarter_grid_round_trip() {
+        let text = "x--x\n-H--\n-|--\n--x-\n";
+        let map = from_pattern_text(text, 150.0, 4).unwrap();
+        assert_eq!(to_pattern_text(&map, ROWS_PER_BEAT), text);
+    }
+
+    #[test]
+    fn negative_duration_hold_is_written_as_a_note() {
+        let mut map = from_pattern_text("x---\n", 150.0, 4).unwrap();
+        map.hit_objects.push(new_hold(300.0, -200.0, 1, 4));
+        assert_eq!(to_pattern_text(&map, ROWS_PER_BEAT), "x---\n----\n----\n-x--\n");
+    }
+}
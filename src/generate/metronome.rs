@@ -0,0 +1,53 @@
+use rosu_map::Beatmap;
+use rosu_map::section::general::GameMode;
+use rosu_map::section::timing_points::TimingPoint;
+
+use crate::transform::fill::{FillPattern, fill_gap};
+
+/// Builds a practice map of constant 1/4 notes at `bpm`
+///
+/// # Arguments
+/// * `key_count` - Key mode of the generated map
+/// * `bpm` - Tempo of the single red line (placed at 0ms, 4/4)
+/// * `bars` - Length of the map in 4/4 bars
+/// * `pattern` - Pattern the notes follow
+///
+/// # Returns
+/// A new beatmap with `bars * 16` notes
+pub fn metronome_map(key_count: u32, bpm: f64, bars: u32, pattern: FillPattern) -> Beatmap {
+    let beat_len = 60_000.0 / bpm;
+
+    let mut map = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: key_count as f32,
+        version: format!("Metronome {} BPM", bpm),
+        ..Default::default()
+    };
+
+    map.control_points.timing_points.push(TimingPoint::new(
+        0.0,
+        beat_len,
+        false,
+        TimingPoint::DEFAULT_TIME_SIGNATURE,
+    ));
+
+    let end = beat_len * 4.0 * bars as f64;
+    fill_gap(&mut map, 0.0, end, 4, pattern);
+
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timing::bpm::bpm_of;
+
+    #[test]
+    fn four_bars_of_quarters() {
+        let map = metronome_map(4, 180.0, 4, FillPattern::Stream);
+
+        assert_eq!(map.hit_objects.len(), 64);
+        assert_eq!(map.control_points.timing_points.len(), 1);
+        assert!((bpm_of(map.control_points.timing_points[0].beat_len) - 180.0).abs() < 1e-9);
+    }
+}
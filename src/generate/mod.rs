@@ -0,0 +1,5 @@
+pub mod dsl;
+pub mod metronome;
+
+pub use dsl::{PatternError, from_pattern_text, to_pattern_text};
+pub use metronome::metronome_map;
@@ -0,0 +1,124 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::hit_samples::{HitSampleDefaultName, HitSampleInfo, HitSampleInfoName};
+
+/// Number of notes using each hitsound
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct HitsoundUsage {
+    pub normal: usize,
+    pub whistle: usize,
+    pub finish: usize,
+    pub clap: usize,
+    /// Notes playing a custom sample file (keysounds)
+    pub file: usize,
+}
+
+/// Counts how many notes use each hitsound addition
+///
+/// A note with several additions counts once for each of them.
+pub fn used_additions(beatmap: &Beatmap) -> HitsoundUsage {
+    let mut usage = HitsoundUsage::default();
+
+    for hit_object in &beatmap.hit_objects {
+        let has = |name: HitSampleDefaultName| {
+            hit_object.samples.iter().any(|sample| sample.name == HitSampleInfoName::Default(name))
+        };
+
+        usage.normal += has(HitSampleDefaultName::Normal) as usize;
+        usage.whistle += has(HitSampleDefaultName::Whistle) as usize;
+        usage.finish += has(HitSampleDefaultName::Finish) as usize;
+        usage.clap += has(HitSampleDefaultName::Clap) as usize;
+        usage.file += hit_object.samples.iter()
+            .any(|sample| matches!(sample.name, HitSampleInfoName::File(_))) as usize;
+    }
+
+    usage
+}
+
+/// Attaches keysound files from a timed sample list to the notes
+///
+/// Each note takes the sample nearest to its start time, if one lies within
+/// `tolerance_ms`. The file replaces the note's normal sound (keeping its
+/// volume) and any previous keysound; additions are kept. Notes without a
+/// matching sample are left untouched.
+pub fn assign_keysounds(beatmap: &mut Beatmap, samples: &[(f64, String)], tolerance_ms: f64) {
+    let mut samples: Vec<&(f64, String)> = samples.iter().collect();
+    samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+    for hit_object in beatmap.hit_objects.iter_mut() {
+        let time = hit_object.start_time;
+        let after = samples.partition_point(|sample| sample.0 < time);
+
+        let nearest = [after.checked_sub(1), Some(after)]
+            .into_iter()
+            .flatten()
+            .filter_map(|i| samples.get(i))
+            .filter(|sample| (sample.0 - time).abs() <= tolerance_ms)
+            .min_by(|a, b| (a.0 - time).abs().partial_cmp(&(b.0 - time).abs()).unwrap());
+
+        let Some((_, filename)) = nearest else {
+            continue;
+        };
+
+        let volume = hit_object.samples.iter()
+            .find(|sample| matches!(sample.name, HitSampleInfo::HIT_NORMAL | HitSampleInfoName::File(_)))
+            .map_or(0, |sample| sample.volume);
+
+        hit_object.samples.retain(|sample| {
+            !matches!(sample.name, HitSampleInfo::HIT_NORMAL | HitSampleInfoName::File(_))
+        });
+        hit_object.samples.insert(0, HitSampleInfo::new(HitSampleInfoName::File(filename.clone()), None, 1, volume));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_note};
+
+    fn sample(name: HitSampleInfoName) -> HitSampleInfo {
+        HitSampleInfo::new(name, None, 0, 100)
+    }
+
+    /// One note per column, 100ms apart
+    fn four_notes() -> Beatmap {
+        map_4k((0..4).map(|i| new_note(i as f64 * 100.0, i, 4)).collect())
+    }
+
+    #[test]
+    fn counts_each_addition() {
+        let mut map = four_notes();
+        map.hit_objects[0].samples = vec![sample(HitSampleInfo::HIT_NORMAL), sample(HitSampleInfo::HIT_WHISTLE)];
+        map.hit_objects[1].samples = vec![sample(HitSampleInfo::HIT_NORMAL), sample(HitSampleInfo::HIT_CLAP)];
+        map.hit_objects[2].samples = vec![
+            sample(HitSampleInfo::HIT_NORMAL),
+            sample(HitSampleInfo::HIT_WHISTLE),
+            sample(HitSampleInfo::HIT_FINISH),
+        ];
+        map.hit_objects[3].samples = vec![sample(HitSampleInfoName::File("kick.wav".to_string()))];
+
+        assert_eq!(
+            used_additions(&map),
+            HitsoundUsage { normal: 3, whistle: 2, finish: 1, clap: 1, file: 1 }
+        );
+    }
+
+    #[test]
+    fn nearest_keysound_replaces_the_normal_sound() {
+        let mut map = four_notes();
+        map.hit_objects[0].samples = vec![sample(HitSampleInfo::HIT_NORMAL), sample(HitSampleInfo::HIT_WHISTLE)];
+        let samples = vec![(5.0, "kick.wav".to_string()), (195.0, "snare.wav".to_string())];
+
+        assign_keysounds(&mut map, &samples, 10.0);
+
+        let names: Vec<Vec<HitSampleInfoName>> = map.hit_objects.iter()
+            .map(|hit_object| hit_object.samples.iter().map(|sample| sample.name.clone()).collect())
+            .collect();
+        assert_eq!(names, vec![
+            vec![HitSampleInfoName::File("kick.wav".to_string()), HitSampleInfo::HIT_WHISTLE],
+            vec![],
+            vec![HitSampleInfoName::File("snare.wav".to_string())],
+            vec![],
+        ]);
+        assert_eq!(map.hit_objects[0].samples[0].volume, 100);
+    }
+}
@@ -0,0 +1,124 @@
+pub mod hitsounds;
+
+use std::io;
+use std::path::Path;
+
+use rosu_map::Beatmap;
+
+use crate::mania::column::MAX_KEYS;
+
+/// A difficulty setting that was out of range and got clamped
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClampWarning {
+    pub field: &'static str,
+    pub original: f32,
+    pub clamped: f32,
+}
+
+/// A parsed beatmap along with the sanitization applied to it
+#[derive(Clone, Debug)]
+pub struct LoadedBeatmap {
+    pub beatmap: Beatmap,
+    pub warnings: Vec<ClampWarning>,
+}
+
+/// Parses a `.osu` file
+///
+/// With `sanitize`, out-of-range difficulty settings are clamped (see
+/// `sanitize_difficulty`) and reported in `warnings`.
+pub fn load_beatmap(path: impl AsRef<Path>, sanitize: bool) -> Result<LoadedBeatmap, io::Error> {
+    let mut beatmap = Beatmap::from_path(path)?;
+    let warnings = if sanitize { sanitize_difficulty(&mut beatmap) } else { Vec::new() };
+
+    Ok(LoadedBeatmap { beatmap, warnings })
+}
+
+/// Clamps OD and HP to 0-10 and the circle size (key count) to 1-18
+///
+/// # Returns
+/// One warning per clamped setting, each also logged
+pub fn sanitize_difficulty(beatmap: &mut Beatmap) -> Vec<ClampWarning> {
+    let mut warnings = Vec::new();
+    let fields = [
+        ("overall_difficulty", &mut beatmap.overall_difficulty, 0.0, 10.0),
+        ("hp_drain_rate", &mut beatmap.hp_drain_rate, 0.0, 10.0),
+        ("circle_size", &mut beatmap.circle_size, 1.0, MAX_KEYS as f32),
+    ];
+
+    for (field, value, min, max) in fields {
+        let clamped = value.clamp(min, max);
+        if clamped != *value {
+            log::warn!("{}: {} {} out of range, clamped to {}", beatmap.version, field, value, clamped);
+            warnings.push(ClampWarning { field, original: *value, clamped });
+            *value = clamped;
+        }
+    }
+
+    warnings
+}
+
+/// Distinct audio files referenced by the beatmaps, in order of first appearance
+///
+/// A marathon plays a single audio file, so more than one entry means the
+/// sources need to be merged into one track.
+pub fn audio_filenames(beatmaps: &[Beatmap]) -> Vec<String> {
+    let mut filenames: Vec<String> = Vec::new();
+
+    for beatmap in beatmaps {
+        if !filenames.contains(&beatmap.audio_file) {
+            filenames.push(beatmap.audio_file.clone());
+        }
+    }
+
+    filenames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_audio(audio_file: &str) -> Beatmap {
+        Beatmap {
+            audio_file: audio_file.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn audio_filenames_lists_each_file_once() {
+        let maps = vec![with_audio("a.mp3"), with_audio("b.ogg"), with_audio("a.mp3")];
+
+        assert_eq!(audio_filenames(&maps), vec!["a.mp3".to_string(), "b.ogg".to_string()]);
+    }
+
+    /// `assets/smk.osu` with its circle size set to 20, written to a temp file
+    fn write_cs_20_map(name: &str) -> std::path::PathBuf {
+        let source = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/smk.osu")).unwrap();
+        let path = std::env::temp_dir().join(format!("mania-changer-{}-{}.osu", name, std::process::id()));
+        std::fs::write(&path, source.replace("CircleSize:4", "CircleSize:20")).unwrap();
+        path
+    }
+
+    #[test]
+    fn circle_size_20_is_clamped_to_18() {
+        let path = write_cs_20_map("sanitized");
+        let loaded = load_beatmap(&path, true).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.beatmap.circle_size, 18.0);
+        assert_eq!(
+            loaded.warnings,
+            vec![ClampWarning { field: "circle_size", original: 20.0, clamped: 18.0 }]
+        );
+    }
+
+    #[test]
+    fn loading_without_sanitizing_keeps_circle_size_20() {
+        let path = write_cs_20_map("raw");
+        let loaded = load_beatmap(&path, false).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.beatmap.circle_size, 20.0);
+        assert!(loaded.warnings.is_empty());
+    }
+}
@@ -7,3 +7,29 @@ pub mod ln;
 #[cfg(feature = "marathon")]
 pub mod marathon;
 
+#[cfg(feature = "mania")]
+pub mod mania;
+
+#[cfg(feature = "timing")]
+pub mod timing;
+
+#[cfg(feature = "analysis")]
+pub mod analysis;
+
+#[cfg(feature = "render")]
+pub mod render;
+
+#[cfg(feature = "transform")]
+pub mod transform;
+
+#[cfg(feature = "sv")]
+pub mod sv;
+
+#[cfg(feature = "io")]
+pub mod io;
+
+#[cfg(feature = "generate")]
+pub mod generate;
+
+#[cfg(feature = "transform")]
+mod rng;
@@ -0,0 +1,198 @@
+use rosu_map::Beatmap;
+use rosu_map::section::general::GameMode;
+use rosu_map::section::hit_objects::{HitObject, HitObjectKind};
+
+/// Highest key count supported by osu!mania
+pub const MAX_KEYS: u32 = 18;
+
+/// Key count declared by the map (osu!mania stores it in `circle_size`)
+pub fn key_count(beatmap: &Beatmap) -> u32 {
+    (beatmap.circle_size.round() as u32).clamp(1, MAX_KEYS)
+}
+
+/// Column of an x position for the given key count
+pub fn column_of(x: f32, key_count: u32) -> u32 {
+    let column = (x / 512.0 * key_count as f32).floor().max(0.0) as u32;
+    column.min(key_count - 1)
+}
+
+/// Canonical x position of a column, as placed by the osu! editor
+///
+/// This is always an integer and satisfies `column_of(x_for_column(c, k), k) == c`,
+/// so column transforms are reversible.
+pub fn x_for_column(column: u32, key_count: u32) -> f32 {
+    (256 * (2 * column + 1) / key_count) as f32
+}
+
+/// X position of a mania note, `None` for sliders and spinners
+pub fn x_of(hit_object: &HitObject) -> Option<f32> {
+    match hit_object.kind {
+        HitObjectKind::Circle(ref circle) => Some(circle.pos.x),
+        HitObjectKind::Hold(ref hold) => Some(hold.pos_x),
+        _ => None,
+    }
+}
+
+/// Column of a mania note, `None` for sliders and spinners
+pub fn column(hit_object: &HitObject, key_count: u32) -> Option<u32> {
+    x_of(hit_object).map(|x| column_of(x, key_count))
+}
+
+/// Moves a mania note to the canonical position of `column`
+pub fn set_column(hit_object: &mut HitObject, column: u32, key_count: u32) {
+    let x = x_for_column(column, key_count);
+
+    match hit_object.kind {
+        HitObjectKind::Circle(ref mut circle) => circle.pos.x = x,
+        HitObjectKind::Hold(ref mut hold) => hold.pos_x = x,
+        _ => {}
+    }
+}
+
+/// Infers the key count from the x positions of the notes
+///
+/// Prefers the smallest key count for which every note sits on a canonical
+/// column position, and falls back to the smallest key count that keeps every
+/// distinct position in its own column. Returns `None` if the map has no notes.
+pub fn inferred_key_count(beatmap: &Beatmap) -> Option<u32> {
+    let mut positions: Vec<i32> = beatmap.hit_objects.iter()
+        .filter_map(x_of)
+        .map(|x| x.round() as i32)
+        .collect();
+    positions.sort_unstable();
+    positions.dedup();
+
+    if positions.is_empty() {
+        return None;
+    }
+
+    let canonical = (1..=MAX_KEYS).find(|&keys| {
+        positions.iter().all(|&x| {
+            x_for_column(column_of(x as f32, keys), keys) as i32 == x
+        })
+    });
+
+    if canonical.is_some() {
+        return canonical;
+    }
+
+    (1..=MAX_KEYS)
+        .filter(|&keys| keys as usize >= positions.len())
+        .find(|&keys| {
+            let mut columns: Vec<u32> = positions.iter().map(|&x| column_of(x as f32, keys)).collect();
+            columns.dedup();
+            columns.len() == positions.len()
+        })
+}
+
+/// Detects the key mode of a mania map (4 for 4K, 7 for 7K, ...)
+///
+/// The key count inferred from the notes wins over `circle_size` when they
+/// disagree, and the discrepancy is logged. Returns `None` for non-mania maps.
+pub fn detect_mania_keymode(beatmap: &Beatmap) -> Option<u32> {
+    if beatmap.mode != GameMode::Mania {
+        return None;
+    }
+
+    let declared = key_count(beatmap);
+
+    match inferred_key_count(beatmap) {
+        Some(inferred) if inferred != declared => {
+            log::warn!(
+                "{}: circle size says {}K but notes suggest {}K, using {}K",
+                beatmap.version, declared, inferred, inferred
+            );
+            Some(inferred)
+        }
+        Some(inferred) => Some(inferred),
+        None => Some(declared),
+    }
+}
+
+/// Hand playing a column
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+/// Hand playing a column, `None` for the center column of odd key counts
+pub fn hand_of(column: u32, key_count: u32) -> Option<Hand> {
+    if column < key_count / 2 {
+        Some(Hand::Left)
+    } else if column >= key_count.div_ceil(2) {
+        Some(Hand::Right)
+    } else {
+        None
+    }
+}
+
+/// First note of `column` starting strictly after `after`
+pub fn next_note_in_column(beatmap: &Beatmap, after: f64, column: u32) -> Option<&HitObject> {
+    let keys = key_count(beatmap);
+
+    beatmap.hit_objects.iter()
+        .filter(|hit_object| hit_object.start_time > after)
+        .filter(|hit_object| self::column(hit_object, keys) == Some(column))
+        .min_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap())
+}
+
+/// Number of notes in each column
+pub fn column_histogram(beatmap: &Beatmap) -> Vec<usize> {
+    let keys = key_count(beatmap);
+    let mut histogram = vec![0; keys as usize];
+
+    for hit_object in &beatmap.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            histogram[column as usize] += 1;
+        }
+    }
+
+    histogram
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    fn mania_map(circle_size: f32) -> Beatmap {
+        Beatmap {
+            mode: GameMode::Mania,
+            circle_size,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keymode_prefers_notes_over_circle_size() {
+        let mut map = mania_map(4.0);
+        for column in 0..7 {
+            map.hit_objects.push(new_note(column as f64 * 100.0, column, 7));
+        }
+
+        assert_eq!(detect_mania_keymode(&map), Some(7));
+    }
+
+    #[test]
+    fn keymode_of_non_mania_map_is_none() {
+        let mut map = mania_map(4.0);
+        map.mode = GameMode::Osu;
+        map.hit_objects.push(new_note(0.0, 0, 4));
+
+        assert_eq!(detect_mania_keymode(&map), None);
+    }
+
+    #[test]
+    fn next_note_in_column_after_time() {
+        let mut map = mania_map(4.0);
+        map.hit_objects.push(new_note(3000.0, 2, 4));
+        map.hit_objects.push(new_note(1000.0, 2, 4));
+        map.hit_objects.push(new_note(1500.0, 1, 4));
+        map.hit_objects.push(new_note(2000.0, 2, 4));
+
+        let next = next_note_in_column(&map, 1000.0, 2).unwrap();
+        assert_eq!(next.start_time, 2000.0);
+        assert!(next_note_in_column(&map, 3000.0, 2).is_none());
+    }
+}
@@ -0,0 +1,118 @@
+use rosu_map::Beatmap;
+
+use super::column::{column, key_count, set_column};
+use super::end_time;
+
+/// Sets the same OD and HP on every beatmap, typically before concatenation
+///
+/// `concat_beatmaps` keeps the settings of the first map, so unifying them
+/// first makes the judgement windows and drain explicit.
+pub fn unify_difficulty_settings(beatmaps: &mut [Beatmap], od: f32, hp: f32) {
+    for beatmap in beatmaps.iter_mut() {
+        beatmap.overall_difficulty = od;
+        beatmap.hp_drain_rate = hp;
+    }
+}
+
+/// Converts a map to `target_keys` columns
+///
+/// Each column maps to a proportional range of target columns. When adding
+/// keys, notes of one column cycle through its range, turning jacks into
+/// trills; when removing keys, notes overlapping an earlier note of the merged
+/// column are dropped.
+pub fn convert_keys(beatmap: &mut Beatmap, target_keys: u32) {
+    let keys = key_count(beatmap);
+    if keys == target_keys {
+        return;
+    }
+
+    beatmap.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+
+    let mut next_in_range = vec![0; keys as usize];
+    let mut occupied_until = vec![f64::NEG_INFINITY; target_keys as usize];
+    let mut kept = vec![true; beatmap.hit_objects.len()];
+
+    for (i, hit_object) in beatmap.hit_objects.iter_mut().enumerate() {
+        let Some(c) = column(hit_object, keys) else {
+            continue;
+        };
+
+        let lo = c * target_keys / keys;
+        let hi = ((c + 1) * target_keys / keys).max(lo + 1);
+        let target = lo + next_in_range[c as usize] % (hi - lo);
+        next_in_range[c as usize] += 1;
+
+        if hit_object.start_time <= occupied_until[target as usize] {
+            kept[i] = false;
+            continue;
+        }
+
+        set_column(hit_object, target, target_keys);
+        occupied_until[target as usize] = end_time(hit_object);
+    }
+
+    let mut kept = kept.into_iter();
+    beatmap.hit_objects.retain(|_| kept.next().unwrap());
+
+    beatmap.circle_size = target_keys as f32;
+    beatmap.version = format!("{} {}K", beatmap.version, target_keys);
+}
+
+/// Converts each beatmap to the key count at the same position in `key_sequence`
+///
+/// Builds escalating medleys (4K, then 6K, then 8K) out of maps of one key
+/// count. Panics if `key_sequence` and `beatmaps` have different lengths.
+pub fn expand_keys_progressive(beatmaps: &mut [Beatmap], key_sequence: &[u32]) {
+    if beatmaps.len() != key_sequence.len() {
+        panic!("Number of key counts must match number of beatmaps");
+    }
+
+    for (beatmap, &keys) in beatmaps.iter_mut().zip(key_sequence) {
+        convert_keys(beatmap, keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_note};
+
+    #[test]
+    fn every_map_shares_od_and_hp() {
+        let mut maps: Vec<Beatmap> = [(7.0, 8.0), (9.0, 6.5), (5.0, 9.5)]
+            .into_iter()
+            .map(|(od, hp)| Beatmap {
+                overall_difficulty: od,
+                hp_drain_rate: hp,
+                ..Default::default()
+            })
+            .collect();
+
+        unify_difficulty_settings(&mut maps, 8.0, 7.5);
+
+        assert!(maps.iter().all(|map| map.overall_difficulty == 8.0 && map.hp_drain_rate == 7.5));
+    }
+
+    fn eight_notes() -> Beatmap {
+        map_4k((0..8).map(|i| new_note(i as f64 * 100.0, i % 4, 4)).collect())
+    }
+
+    #[test]
+    fn progressive_expansion_uses_each_key_count() {
+        let mut maps = vec![eight_notes(), eight_notes(), eight_notes()];
+
+        expand_keys_progressive(&mut maps, &[4, 6, 8]);
+
+        for (map, keys) in maps.iter().zip([4, 6, 8]) {
+            assert_eq!(key_count(map), keys);
+            assert_eq!(map.hit_objects.len(), 8);
+            assert!(map.hit_objects.iter().all(|note| column(note, keys).is_some_and(|c| c < keys)));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn progressive_expansion_needs_one_key_count_per_map() {
+        expand_keys_progressive(&mut [eight_notes(), eight_notes()], &[4, 6, 8]);
+    }
+}
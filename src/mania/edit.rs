@@ -0,0 +1,38 @@
+use rosu_map::Beatmap;
+
+use super::column::{column, key_count};
+
+/// Number of notes starting in `[start, end]`, optionally only in `columns`
+///
+/// Both ends are inclusive, like an editor selection. Sliders and spinners
+/// are never counted.
+pub fn count_in_range(beatmap: &Beatmap, start: f64, end: f64, columns: Option<&[u32]>) -> usize {
+    let keys = key_count(beatmap);
+
+    beatmap.hit_objects.iter()
+        .filter(|hit_object| hit_object.start_time >= start && hit_object.start_time <= end)
+        .filter_map(|hit_object| column(hit_object, keys))
+        .filter(|c| columns.is_none_or(|columns| columns.contains(c)))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    #[test]
+    fn counts_notes_in_window_and_columns() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        for i in 0..8 {
+            map.hit_objects.push(new_note(i as f64 * 100.0, i % 4, 4));
+        }
+
+        assert_eq!(count_in_range(&map, 100.0, 500.0, None), 5);
+        assert_eq!(count_in_range(&map, 100.0, 500.0, Some(&[1, 2])), 3);
+        assert_eq!(count_in_range(&map, 800.0, 900.0, None), 0);
+    }
+}
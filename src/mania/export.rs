@@ -0,0 +1,71 @@
+use rosu_map::Beatmap;
+use rosu_map::section::general::GameMode;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use super::column::{column, key_count};
+use super::{new_hold, new_note};
+
+/// A note as `(time, column, hold_duration)`, `None` for circles
+pub type GridNote = (f64, u32, Option<f64>);
+
+/// Converts the notes of a map to a compact column grid, sorted by time then column
+pub fn to_column_grid(beatmap: &Beatmap) -> Vec<GridNote> {
+    let keys = key_count(beatmap);
+
+    let mut grid: Vec<GridNote> = beatmap.hit_objects.iter()
+        .filter_map(|hit_object| {
+            let column = column(hit_object, keys)?;
+            let duration = match hit_object.kind {
+                HitObjectKind::Hold(ref hold) => Some(hold.duration),
+                _ => None,
+            };
+            Some((hit_object.start_time, column, duration))
+        })
+        .collect();
+
+    grid.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+    grid
+}
+
+/// Builds a mania beatmap from a column grid
+///
+/// The result only carries the notes and the key count; timing points and
+/// metadata are left at their defaults.
+pub fn from_column_grid(grid: &[GridNote], key_count: u32) -> Beatmap {
+    let mut beatmap = Beatmap {
+        mode: GameMode::Mania,
+        circle_size: key_count as f32,
+        ..Default::default()
+    };
+
+    beatmap.hit_objects = grid.iter()
+        .map(|&(time, column, duration)| match duration {
+            Some(duration) => new_hold(time, duration, column, key_count),
+            None => new_note(time, column, key_count),
+        })
+        .collect();
+    beatmap.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+
+    beatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_round_trip() {
+        let grid: Vec<GridNote> = vec![
+            (0.0, 0, None),
+            (0.0, 3, Some(250.0)),
+            (125.0, 1, None),
+            (250.0, 2, Some(500.0)),
+        ];
+
+        let map = from_column_grid(&grid, 4);
+
+        assert_eq!(map.mode, GameMode::Mania);
+        assert_eq!(key_count(&map), 4);
+        assert_eq!(to_column_grid(&map), grid);
+    }
+}
@@ -0,0 +1,56 @@
+pub mod column;
+pub mod convert;
+pub mod edit;
+pub mod export;
+pub mod validate;
+
+use rosu_map::section::hit_objects::{HitObject, HitObjectCircle, HitObjectHold, HitObjectKind};
+use rosu_map::util::Pos;
+
+use column::x_for_column;
+
+/// End time of a hit object, including the body of holds
+pub fn end_time(hit_object: &HitObject) -> f64 {
+    match hit_object.kind {
+        HitObjectKind::Hold(ref hold) => hit_object.start_time + hold.duration,
+        _ => hit_object.start_time,
+    }
+}
+
+/// New circle in `column`, without hitsounds
+pub fn new_note(time: f64, column: u32, key_count: u32) -> HitObject {
+    HitObject {
+        start_time: time,
+        kind: HitObjectKind::Circle(HitObjectCircle {
+            pos: Pos {
+                x: x_for_column(column, key_count),
+                y: 0.0,
+            },
+            new_combo: false,
+            combo_offset: 0,
+        }),
+        samples: Vec::new(),
+    }
+}
+
+/// New hold in `column`, without hitsounds
+pub fn new_hold(time: f64, duration: f64, column: u32, key_count: u32) -> HitObject {
+    HitObject {
+        start_time: time,
+        kind: HitObjectKind::Hold(HitObjectHold {
+            pos_x: x_for_column(column, key_count),
+            duration,
+        }),
+        samples: Vec::new(),
+    }
+}
+
+/// 4K map holding `hit_objects`, for tests
+#[cfg(test)]
+pub(crate) fn map_4k(hit_objects: Vec<HitObject>) -> rosu_map::Beatmap {
+    rosu_map::Beatmap {
+        circle_size: 4.0,
+        hit_objects,
+        ..Default::default()
+    }
+}
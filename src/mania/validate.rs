@@ -0,0 +1,237 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::{HitObjectCircle, HitObjectKind};
+use rosu_map::util::Pos;
+
+use super::column::{column, key_count};
+
+/// Times at which two notes start in the same column within `tolerance_ms`
+///
+/// A valid chord never has two notes in one column, so any hit here is a
+/// mapping or transform bug. Each collision is reported once, at the time of
+/// its first note, in time order.
+pub fn find_chord_column_collisions(beatmap: &Beatmap, tolerance_ms: f64) -> Vec<f64> {
+    let keys = key_count(beatmap);
+    let mut columns: Vec<Vec<f64>> = vec![Vec::new(); keys as usize];
+
+    for hit_object in &beatmap.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            columns[column as usize].push(hit_object.start_time);
+        }
+    }
+
+    let mut collisions = Vec::new();
+    for times in columns.iter_mut() {
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in times.windows(2) {
+            if pair[1] - pair[0] <= tolerance_ms {
+                collisions.push(pair[0]);
+            }
+        }
+    }
+
+    collisions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    collisions.dedup();
+    collisions
+}
+
+/// Indices of the notes starting before the first red line
+///
+/// The BPM of those notes is ambiguous, which breaks snapping. Maps without
+/// red lines report nothing.
+pub fn notes_before_timing(beatmap: &Beatmap) -> Vec<usize> {
+    let Some(first) = beatmap.control_points.timing_points.first() else {
+        return Vec::new();
+    };
+
+    beatmap.hit_objects.iter()
+        .enumerate()
+        .filter(|(_, hit_object)| hit_object.start_time < first.time)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Moves the first red line back by whole beats so it comes before every note
+///
+/// The beat grid is unchanged, so the early notes keep their snapping.
+pub fn extend_first_timing(beatmap: &mut Beatmap) {
+    let Some(first_note) = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+    else {
+        return;
+    };
+    let Some(point) = beatmap.control_points.timing_points.first_mut() else {
+        return;
+    };
+
+    if first_note < point.time {
+        let beats = ((point.time - first_note) / point.beat_len).ceil();
+        point.time -= beats * point.beat_len;
+    }
+}
+
+/// Clamps hold durations so every hold ends between its start and `map_end`
+///
+/// Corrupt durations (negative, NaN, or running past the end of the map)
+/// confuse renderers. Holds starting after `map_end` get a zero duration.
+pub fn clamp_hold_ends(beatmap: &mut Beatmap, map_end: f64) {
+    for hit_object in beatmap.hit_objects.iter_mut() {
+        if let HitObjectKind::Hold(ref mut hold) = hit_object.kind {
+            let max_duration = (map_end - hit_object.start_time).max(0.0);
+
+            hold.duration = if hold.duration.is_nan() {
+                0.0
+            } else {
+                hold.duration.clamp(0.0, max_duration)
+            };
+        }
+    }
+}
+
+/// Indices of the holds shorter than `min_ms`
+///
+/// Very short holds look like circles and confuse players.
+pub fn find_short_holds(beatmap: &Beatmap, min_ms: f64) -> Vec<usize> {
+    beatmap.hit_objects.iter()
+        .enumerate()
+        .filter(|(_, hit_object)| matches!(hit_object.kind, HitObjectKind::Hold(ref hold) if hold.duration < min_ms))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Turns every hold shorter than `min_ms` into a circle at the same position
+///
+/// # Returns
+/// The number of holds converted
+pub fn short_holds_to_circles(beatmap: &mut Beatmap, min_ms: f64) -> usize {
+    let short = find_short_holds(beatmap, min_ms);
+
+    for &i in &short {
+        let hit_object = &mut beatmap.hit_objects[i];
+        if let HitObjectKind::Hold(ref hold) = hit_object.kind {
+            hit_object.kind = HitObjectKind::Circle(HitObjectCircle {
+                pos: Pos { x: hold.pos_x, y: 0.0 },
+                new_combo: false,
+                combo_offset: 0,
+            });
+        }
+    }
+
+    short.len()
+}
+
+/// Whether the hit objects and every control point list are in time order
+///
+/// Equal times are allowed. Cheap enough to check after every transform.
+pub fn is_sorted(beatmap: &Beatmap) -> bool {
+    let control_points = &beatmap.control_points;
+
+    first_unsorted(beatmap).is_none()
+        && control_points.timing_points.is_sorted_by(|a, b| a.time <= b.time)
+        && control_points.effect_points.is_sorted_by(|a, b| a.time <= b.time)
+        && control_points.difficulty_points.is_sorted_by(|a, b| a.time <= b.time)
+        && control_points.sample_points.is_sorted_by(|a, b| a.time <= b.time)
+}
+
+/// Index of the first hit object starting before the one preceding it
+///
+/// Only looks at the hit objects: a map with unsorted control points gets
+/// `None` here but still fails `is_sorted`.
+pub fn first_unsorted(beatmap: &Beatmap) -> Option<usize> {
+    beatmap.hit_objects.windows(2)
+        .position(|pair| pair[1].start_time < pair[0].start_time)
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{end_time, map_4k, new_hold, new_note};
+    use rosu_map::section::timing_points::TimingPoint;
+
+    #[test]
+    fn reports_two_notes_in_one_column_of_a_chord() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects.push(new_note(1000.0, 0, 4));
+        map.hit_objects.push(new_note(1000.0, 1, 4));
+        map.hit_objects.push(new_note(1000.5, 1, 4));
+        map.hit_objects.push(new_note(2000.0, 1, 4));
+
+        assert_eq!(find_chord_column_collisions(&map, 1.0), vec![1000.0]);
+    }
+
+    #[test]
+    fn extend_first_timing_covers_early_notes() {
+        let mut map = map_4k(Vec::new());
+        map.control_points.timing_points.push(TimingPoint::new(
+            1000.0,
+            300.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        for time in [200.0, 900.0, 1000.0] {
+            map.hit_objects.push(new_note(time, 0, 4));
+        }
+        assert_eq!(notes_before_timing(&map), vec![0, 1]);
+
+        extend_first_timing(&mut map);
+
+        assert_eq!(map.control_points.timing_points[0].time, 100.0);
+        assert!(notes_before_timing(&map).is_empty());
+    }
+
+    #[test]
+    fn clamp_hold_ends_cuts_holds_at_the_map_end() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects = vec![
+            new_hold(0.0, 500.0, 0, 4),
+            new_hold(800.0, 500.0, 1, 4),
+            new_hold(1200.0, 100.0, 2, 4),
+            new_hold(300.0, -50.0, 3, 4),
+        ];
+
+        clamp_hold_ends(&mut map, 1000.0);
+
+        let ends: Vec<f64> = map.hit_objects.iter().map(end_time).collect();
+        assert_eq!(ends, vec![500.0, 1000.0, 1200.0, 300.0]);
+    }
+
+    #[test]
+    fn ten_millisecond_hold_becomes_a_circle() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects = vec![
+            new_hold(0.0, 10.0, 0, 4),
+            new_hold(100.0, 200.0, 1, 4),
+            new_note(200.0, 2, 4),
+        ];
+        assert_eq!(find_short_holds(&map, 30.0), vec![0]);
+
+        assert_eq!(short_holds_to_circles(&mut map, 30.0), 1);
+
+        assert!(matches!(map.hit_objects[0].kind, HitObjectKind::Circle(_)));
+        assert_eq!(column(&map.hit_objects[0], 4), Some(0));
+        assert_eq!(end_time(&map.hit_objects[1]), 300.0);
+        assert!(find_short_holds(&map, 30.0).is_empty());
+    }
+
+    #[test]
+    fn finds_the_first_out_of_order_note() {
+        let mut map = map_4k(Vec::new());
+        for time in [0.0, 100.0, 100.0, 50.0, 200.0, 10.0] {
+            map.hit_objects.push(new_note(time, 0, 4));
+        }
+        assert_eq!(first_unsorted(&map), Some(3));
+        assert!(!is_sorted(&map));
+
+        map.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+        assert_eq!(first_unsorted(&map), None);
+        assert!(is_sorted(&map));
+
+        map.control_points.timing_points = vec![
+            TimingPoint::new(1000.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+        ];
+        assert_eq!(first_unsorted(&map), None);
+        assert!(!is_sorted(&map));
+    }
+}
@@ -0,0 +1,58 @@
+use rosu_map::Beatmap;
+
+use super::marathon::{get_beatmap_duration, projected_marathon_duration};
+
+/// Share of the marathon each creator mapped, for credits
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps in marathon order
+/// * `gap_ms` - Wait time between each beatmap in milliseconds
+///
+/// # Returns
+/// `(creator, percentage)` pairs in order of first appearance, maps by the same
+/// creator aggregated. Percentages are of the whole marathon length, so with
+/// gaps they add up to less than 100.
+pub fn marathon_contributions(beatmaps: &[Beatmap], gap_ms: f64) -> Vec<(String, f64)> {
+    let total = projected_marathon_duration(beatmaps, gap_ms);
+    let mut contributions: Vec<(String, f64)> = Vec::new();
+
+    for beatmap in beatmaps {
+        let duration = get_beatmap_duration(beatmap);
+
+        match contributions.iter_mut().find(|(creator, _)| *creator == beatmap.creator) {
+            Some((_, time)) => *time += duration,
+            None => contributions.push((beatmap.creator.clone(), duration)),
+        }
+    }
+
+    for (_, time) in contributions.iter_mut() {
+        *time = if total > 0.0 { *time / total * 100.0 } else { 0.0 };
+    }
+
+    contributions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    /// Map by `creator` whose last note is at `duration_ms`
+    fn map_by(creator: &str, duration_ms: f64) -> Beatmap {
+        Beatmap {
+            circle_size: 4.0,
+            creator: creator.to_string(),
+            hit_objects: vec![new_note(0.0, 0, 4), new_note(duration_ms, 1, 4)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn creators_are_aggregated() {
+        let maps = vec![map_by("alice", 3000.0), map_by("bob", 4000.0), map_by("alice", 3000.0)];
+
+        let contributions = marathon_contributions(&maps, 0.0);
+
+        assert_eq!(contributions, vec![("alice".to_string(), 60.0), ("bob".to_string(), 40.0)]);
+    }
+}
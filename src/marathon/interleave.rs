@@ -0,0 +1,163 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::{column, key_count, set_column};
+use crate::mania::end_time;
+
+/// Notes closer than this in the same column are considered colliding
+pub const COLLISION_TOLERANCE_MS: f64 = 1.0;
+
+/// Overlays two maps on the same timeline
+///
+/// Hit objects and control points of both maps are merged without any time offset.
+/// When a note of `b` collides with a note already in that column, it is moved to
+/// the nearest free column, or dropped if every column is busy.
+///
+/// # Arguments
+/// * `a` - Base beatmap, its metadata is kept
+/// * `b` - Beatmap overlaid on top of `a`, must have the same key count
+///
+/// # Returns
+/// A new beatmap containing the notes of both maps
+pub fn interleave(a: &Beatmap, b: &Beatmap) -> Beatmap {
+    let keys = key_count(a);
+    if keys != key_count(b) {
+        panic!("Cannot interleave beatmaps with different key counts");
+    }
+
+    let mut result = a.clone();
+    let mut columns: Vec<Vec<(f64, f64)>> = vec![Vec::new(); keys as usize];
+
+    for hit_object in &result.hit_objects {
+        if let Some(column) = column(hit_object, keys) {
+            columns[column as usize].push((hit_object.start_time, end_time(hit_object)));
+        }
+    }
+
+    for hit_object in &b.hit_objects {
+        let Some(column) = column(hit_object, keys) else {
+            continue;
+        };
+
+        let span = (hit_object.start_time, end_time(hit_object));
+        let free = |c: usize| !columns[c].iter().any(|&placed| overlaps(placed, span));
+
+        let target = (0..keys as i64)
+            .flat_map(|d| [column as i64 - d, column as i64 + d])
+            .filter(|&c| c >= 0 && c < keys as i64)
+            .map(|c| c as usize)
+            .find(|&c| free(c));
+
+        if let Some(target) = target {
+            let mut hit_object = hit_object.clone();
+            if target as u32 != column {
+                set_column(&mut hit_object, target as u32, keys);
+            }
+            columns[target].push(span);
+            result.hit_objects.push(hit_object);
+        }
+    }
+
+    result.control_points.timing_points.extend(b.control_points.timing_points.iter().cloned());
+    result.control_points.effect_points.extend(b.control_points.effect_points.iter().cloned());
+    result.control_points.difficulty_points.extend(b.control_points.difficulty_points.iter().cloned());
+    result.control_points.sample_points.extend(b.control_points.sample_points.iter().cloned());
+
+    result.control_points.timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    result.control_points.effect_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    result.control_points.difficulty_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    result.control_points.sample_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+    result.control_points.timing_points.dedup();
+    result.control_points.effect_points.dedup();
+    result.control_points.difficulty_points.dedup();
+    result.control_points.sample_points.dedup();
+
+    result.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+
+    result.version = format!("{} + {}", a.version, b.version);
+
+    result
+}
+
+fn overlaps(a: (f64, f64), b: (f64, f64)) -> bool {
+    a.0 <= b.1 + COLLISION_TOLERANCE_MS && b.0 <= a.1 + COLLISION_TOLERANCE_MS
+}
+
+/// Replaces the notes of `target` in a region with those of `source`
+///
+/// Both maps share the same timeline, so the notes of `source` starting in
+/// `[region.0, region.1)` are copied at their own times. Notes of `target`
+/// starting in the region are removed; its control points are kept. Holds
+/// crossing a boundary of the region are left as they are.
+///
+/// # Arguments
+/// * `target` - Beatmap being remixed
+/// * `source` - Beatmap the region is taken from, must have the same key count
+/// * `region` - `(start, end)` of the region in milliseconds
+pub fn splice_region(target: &mut Beatmap, source: &Beatmap, region: (f64, f64)) {
+    if key_count(target) != key_count(source) {
+        panic!("Cannot splice beatmaps with different key counts");
+    }
+
+    let (start, end) = region;
+    let inside = |time: f64| time >= start && time < end;
+
+    target.hit_objects.retain(|hit_object| !inside(hit_object.start_time));
+    target.hit_objects.extend(
+        source.hit_objects.iter()
+            .filter(|hit_object| inside(hit_object.start_time))
+            .cloned(),
+    );
+    target.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{map_4k, new_hold, new_note};
+
+    #[test]
+    fn interleave_merges_and_bumps_collisions() {
+        let a = map_4k(vec![new_note(0.0, 0, 4), new_note(100.0, 1, 4)]);
+        let b = map_4k(vec![new_note(0.0, 0, 4), new_note(200.0, 3, 4)]);
+
+        let result = interleave(&a, &b);
+        let notes: Vec<(f64, Option<u32>)> = result.hit_objects.iter()
+            .map(|h| (h.start_time, column(h, 4)))
+            .collect();
+
+        assert_eq!(notes, vec![(0.0, Some(0)), (0.0, Some(1)), (100.0, Some(1)), (200.0, Some(3))]);
+    }
+
+    #[test]
+    fn interleave_drops_notes_when_every_column_is_busy() {
+        let a = map_4k((0..4).map(|c| new_hold(0.0, 500.0, c, 4)).collect());
+        let b = map_4k(vec![new_note(250.0, 2, 4)]);
+
+        assert_eq!(interleave(&a, &b).hit_objects.len(), 4);
+    }
+
+    #[test]
+    fn splice_replaces_the_middle_section() {
+        let mut target = map_4k((0..10).map(|i| new_note(i as f64 * 100.0, 0, 4)).collect());
+        let source = map_4k((0..10).map(|i| new_note(i as f64 * 100.0 + 50.0, 3, 4)).collect());
+
+        splice_region(&mut target, &source, (300.0, 600.0));
+
+        let notes: Vec<(f64, Option<u32>)> = target.hit_objects.iter()
+            .map(|h| (h.start_time, column(h, 4)))
+            .collect();
+        assert_eq!(notes, vec![
+            (0.0, Some(0)),
+            (100.0, Some(0)),
+            (200.0, Some(0)),
+            (350.0, Some(3)),
+            (450.0, Some(3)),
+            (550.0, Some(3)),
+            (600.0, Some(0)),
+            (700.0, Some(0)),
+            (800.0, Some(0)),
+            (900.0, Some(0)),
+        ]);
+    }
+}
@@ -71,12 +71,75 @@ pub fn concat_beatmaps(beatmaps: Vec<Beatmap>, gap_ms: Option<f64>) -> Beatmap {
 
     // Update metadata
     result.version = format!("{} Marathon ({} maps)", result.version, beatmaps.len());
+    set_audio_metadata(&mut result, &beatmaps[0]);
 
     result
 }
 
+/// Concatenates beatmaps like `concat_beatmaps`, also reporting where each map starts
+///
+/// # Arguments
+/// * `beatmaps` - A vector of beatmaps to concatenate
+/// * `gap_ms` - Wait time between each beatmap in milliseconds (optional, default: 0.0)
+/// * `tag_sources` - Whether to also compute the source map of every hit object
+///
+/// # Returns
+/// The marathon, the start time of every map after the first (as passed to
+/// `unmarathon`), and with `tag_sources` the index of the source map of each
+/// hit object, parallel to the sorted `hit_objects` of the marathon
+pub fn concat_beatmaps_with_boundaries(
+    beatmaps: Vec<Beatmap>,
+    gap_ms: Option<f64>,
+    tag_sources: bool,
+) -> (Beatmap, Vec<f64>, Option<Vec<usize>>) {
+    let gap = gap_ms.unwrap_or(0.0);
+
+    let mut offsets = Vec::with_capacity(beatmaps.len());
+    let mut offset = 0.0;
+    for beatmap in &beatmaps {
+        offsets.push(offset);
+        offset += get_beatmap_duration(beatmap) + gap;
+    }
+
+    // Same push order and stable sort as `concat_beatmaps`, so the tags line
+    // up with the sorted hit objects
+    let sources = tag_sources.then(|| {
+        let mut tagged: Vec<(f64, usize)> = beatmaps.iter()
+            .zip(&offsets)
+            .enumerate()
+            .flat_map(|(i, (beatmap, &offset))| {
+                beatmap.hit_objects.iter().map(move |hit_object| (hit_object.start_time + offset, i))
+            })
+            .collect();
+        if beatmaps.len() > 1 {
+            tagged.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        }
+        tagged.into_iter().map(|(_, source)| source).collect()
+    });
+
+    let boundaries = offsets.into_iter().skip(1).collect();
+
+    (concat_beatmaps(beatmaps, gap_ms), boundaries, sources)
+}
+
+/// Takes the lead-in and preview point of the marathon from its first map
+///
+/// The merged audio starts with the first map, so its lead-in applies. Its
+/// preview point is kept if set, otherwise the preview starts at the first note.
+fn set_audio_metadata(result: &mut Beatmap, first: &Beatmap) {
+    result.audio_lead_in = first.audio_lead_in;
+    result.preview_time = if first.preview_time >= 0 {
+        first.preview_time
+    } else {
+        first.hit_objects.iter()
+            .map(|hit_object| hit_object.start_time)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .map_or(-1, |time| time as i32)
+    };
+}
+
 /// Calculates the duration of a beatmap in milliseconds
-fn get_beatmap_duration(beatmap: &Beatmap) -> f64 {
+pub(crate) fn get_beatmap_duration(beatmap: &Beatmap) -> f64 {
     if beatmap.hit_objects.is_empty() {
         return 0.0;
     }
@@ -97,6 +160,21 @@ fn get_beatmap_duration(beatmap: &Beatmap) -> f64 {
     max_time
 }
 
+/// Projected length of `concat_beatmaps(beatmaps, Some(gap_ms))` without building it
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps to concatenate
+/// * `gap_ms` - Wait time between each beatmap in milliseconds
+///
+/// # Returns
+/// The end time of the last object of the marathon in milliseconds
+pub fn projected_marathon_duration(beatmaps: &[Beatmap], gap_ms: f64) -> f64 {
+    let total: f64 = beatmaps.iter().map(get_beatmap_duration).sum();
+    let gaps = beatmaps.len().saturating_sub(1) as f64 * gap_ms;
+
+    total + gaps
+}
+
 /// Concatenates multiple beatmaps with custom transitions
 /// 
 /// # Arguments
@@ -165,6 +243,65 @@ pub fn concat_beatmaps_with_transitions(beatmaps: Vec<Beatmap>, transitions: Opt
     result.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
 
     result.version = format!("{} Marathon ({} maps)", result.version, beatmaps.len());
+    set_audio_metadata(&mut result, &beatmaps[0]);
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::column::column;
+    use crate::mania::{map_4k, new_hold, new_note};
+
+    #[test]
+    fn projected_duration_matches_concat() {
+        let maps = vec![
+            map_4k(vec![new_note(0.0, 0, 4), new_note(1000.0, 1, 4)]),
+            map_4k(vec![new_hold(0.0, 2500.0, 2, 4)]),
+            map_4k(vec![new_note(500.0, 3, 4)]),
+        ];
+
+        let projected = projected_marathon_duration(&maps, 200.0);
+        let actual = get_beatmap_duration(&concat_beatmaps(maps, Some(200.0)));
+
+        assert_eq!(projected, 1000.0 + 2500.0 + 500.0 + 2.0 * 200.0);
+        assert_eq!(projected, actual);
+    }
+
+    #[test]
+    fn lead_in_and_preview_come_from_the_first_map() {
+        let mut first = map_4k(vec![new_note(400.0, 0, 4), new_note(1000.0, 1, 4)]);
+        first.audio_lead_in = 1500.0;
+        first.preview_time = 800;
+        let mut second = map_4k(vec![new_note(0.0, 2, 4)]);
+        second.audio_lead_in = 3000.0;
+        second.preview_time = 100;
+
+        let result = concat_beatmaps(vec![first.clone(), second.clone()], Some(0.0));
+        assert_eq!(result.audio_lead_in, 1500.0);
+        assert_eq!(result.preview_time, 800);
+
+        first.preview_time = -1;
+        let result = concat_beatmaps(vec![first, second], Some(0.0));
+        assert_eq!(result.preview_time, 400);
+    }
+
+    #[test]
+    fn sources_line_up_with_sorted_hit_objects() {
+        // Each map plays its own column; with no gap, the last note of a map and
+        // the first note of the next one share a time
+        let beatmaps: Vec<Beatmap> = (0..3)
+            .map(|c| map_4k((0..4).map(|i| new_note(i as f64 * 100.0, c, 4)).collect()))
+            .collect();
+
+        let (marathon, boundaries, sources) = concat_beatmaps_with_boundaries(beatmaps.clone(), None, true);
+
+        assert_eq!(boundaries, vec![300.0, 600.0]);
+        let columns: Vec<usize> = marathon.hit_objects.iter()
+            .map(|hit_object| column(hit_object, 4).unwrap() as usize)
+            .collect();
+        assert_eq!(sources, Some(columns));
+        assert_eq!(concat_beatmaps_with_boundaries(beatmaps, None, false).2, None);
+    }
+}
@@ -1 +1,63 @@
+pub mod cue;
+pub mod interleave;
 pub mod marathon;
+pub mod order;
+pub mod split;
+pub mod validate;
+
+use rosu_map::Beatmap;
+
+use crate::transform::add_outro;
+
+/// Seed of the warm-down notes, fixed so a marathon can be rebuilt identically
+pub const WARMDOWN_SEED: u64 = 0;
+
+/// Concatenates beatmaps, then appends a warm-down whose density ramps to zero
+///
+/// # Arguments
+/// * `beatmaps` - A vector of beatmaps to concatenate
+/// * `gap_ms` - Wait time between each beatmap in milliseconds (optional, default: 0.0)
+/// * `warmdown_ms` - Length of the warm-down after the last map, see `add_outro`
+///
+/// # Returns
+/// The marathon followed by its warm-down
+pub fn concat_with_warmdown(beatmaps: Vec<Beatmap>, gap_ms: Option<f64>, warmdown_ms: f64) -> Beatmap {
+    let mut result = marathon::concat_beatmaps(beatmaps, gap_ms);
+    add_outro(&mut result, warmdown_ms, WARMDOWN_SEED);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+    use super::marathon::{concat_beatmaps, get_beatmap_duration};
+    use crate::mania::{map_4k, new_note};
+
+    /// 120 BPM 4K map of 8 quarter notes
+    fn quarter_notes() -> Beatmap {
+        let mut map = map_4k((0..8).map(|i| new_note(i as f64 * 500.0, i % 4, 4)).collect());
+        map.control_points.timing_points.push(TimingPoint::new(
+            0.0,
+            500.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        map
+    }
+
+    #[test]
+    fn warmdown_thins_out_after_the_last_map() {
+        let marathon_end = get_beatmap_duration(&concat_beatmaps(vec![quarter_notes(), quarter_notes()], Some(1000.0)));
+
+        let result = concat_with_warmdown(vec![quarter_notes(), quarter_notes()], Some(1000.0), 8000.0);
+
+        let tail: Vec<f64> = result.hit_objects[16..].iter().map(|note| note.start_time - marathon_end).collect();
+        assert!(tail.iter().all(|&time| time > 0.0 && time <= 8001.0));
+
+        let quarters: Vec<usize> = (0..4)
+            .map(|q| tail.iter().filter(|&&time| (time / 2000.0) as usize == q).count())
+            .collect();
+        assert!(quarters.windows(2).all(|pair| pair[0] > pair[1]), "{:?}", quarters);
+    }
+}
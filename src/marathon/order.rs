@@ -0,0 +1,177 @@
+use rosu_map::Beatmap;
+
+use super::marathon::{concat_beatmaps, get_beatmap_duration};
+use crate::analysis::difficulty::difficulty_score;
+use crate::rng::Rng;
+use crate::transform::mirror_columns;
+
+/// Sorts beatmaps by `difficulty_score` before concatenation
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps to order
+/// * `ascending` - `true` for easiest first, `false` for hardest first
+///
+/// # Returns
+/// The same beatmaps, ordered by difficulty (ties keep their input order)
+pub fn order_by_difficulty(beatmaps: Vec<Beatmap>, ascending: bool) -> Vec<Beatmap> {
+    let mut scored: Vec<(f64, Beatmap)> = beatmaps.into_iter()
+        .map(|beatmap| (difficulty_score(&beatmap), beatmap))
+        .collect();
+
+    scored.sort_by(|a, b| {
+        let order = a.0.partial_cmp(&b.0).unwrap();
+        if ascending { order } else { order.reverse() }
+    });
+
+    scored.into_iter().map(|(_, beatmap)| beatmap).collect()
+}
+
+/// Keeps the leading beatmaps that fit in a time budget
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps in marathon order
+/// * `max_total_ms` - Maximum length of the marathon in milliseconds
+/// * `gap_ms` - Wait time between each beatmap, as passed to `concat_beatmaps`
+///
+/// # Returns
+/// The beatmaps included before the projected total would exceed the budget
+pub fn limit_duration(beatmaps: Vec<Beatmap>, max_total_ms: f64, gap_ms: f64) -> Vec<Beatmap> {
+    let mut total = 0.0;
+    let mut included = Vec::new();
+
+    for beatmap in beatmaps {
+        let gap = if included.is_empty() { 0.0 } else { gap_ms };
+        let projected = total + gap + get_beatmap_duration(&beatmap);
+
+        if projected > max_total_ms {
+            break;
+        }
+
+        total = projected;
+        included.push(beatmap);
+    }
+
+    included
+}
+
+/// Shuffles the beatmaps, deterministically for a given `seed`
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps to shuffle
+/// * `seed` - Seed of the shuffle, the same seed always gives the same order
+///
+/// # Returns
+/// The same beatmaps in shuffled order
+pub fn shuffle_maps(beatmaps: Vec<Beatmap>, seed: u64) -> Vec<Beatmap> {
+    let mut beatmaps = beatmaps;
+    Rng::new(seed).shuffle(&mut beatmaps);
+    beatmaps
+}
+
+/// Concatenates beatmaps from the hardest to the easiest, for gauntlet sets
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps to concatenate, in any order
+/// * `gap_ms` - Wait time between each beatmap, as passed to `concat_beatmaps`
+///
+/// # Returns
+/// The marathon, starting with the map with the highest `difficulty_score`
+pub fn concat_hardest_first(beatmaps: Vec<Beatmap>, gap_ms: Option<f64>) -> Beatmap {
+    concat_beatmaps(order_by_difficulty(beatmaps, false), gap_ms)
+}
+
+/// Concatenates beatmaps, mirroring every other one for variety
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps in marathon order
+/// * `gap_ms` - Wait time between each beatmap, as passed to `concat_beatmaps`
+///
+/// # Returns
+/// The marathon, where the 2nd, 4th, ... maps have their columns mirrored
+pub fn concat_alternating_mirror(beatmaps: Vec<Beatmap>, gap_ms: Option<f64>) -> Beatmap {
+    let mut beatmaps = beatmaps;
+    for beatmap in beatmaps.iter_mut().skip(1).step_by(2) {
+        mirror_columns(beatmap);
+    }
+
+    concat_beatmaps(beatmaps, gap_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::column::column;
+    use crate::mania::new_note;
+
+    /// 4K stream of `notes` notes, `spacing_ms` apart, named `version`
+    fn stream(version: &str, notes: usize, spacing_ms: f64) -> Beatmap {
+        Beatmap {
+            circle_size: 4.0,
+            version: version.to_string(),
+            hit_objects: (0..notes).map(|i| new_note(i as f64 * spacing_ms, (i % 4) as u32, 4)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn order_by_difficulty_ascending() {
+        let maps = vec![stream("hard", 100, 50.0), stream("easy", 100, 400.0), stream("normal", 100, 150.0)];
+
+        let ordered = order_by_difficulty(maps, true);
+        let scores: Vec<f64> = ordered.iter().map(difficulty_score).collect();
+
+        assert!(scores.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(ordered[0].version, "easy");
+        assert_eq!(ordered[2].version, "hard");
+    }
+
+    #[test]
+    fn limit_duration_keeps_maps_within_budget() {
+        // Each map lasts 9900ms
+        let maps = vec![stream("a", 100, 100.0), stream("b", 100, 100.0), stream("c", 100, 100.0)];
+
+        let included = limit_duration(maps, 25_000.0, 1000.0);
+        let versions: Vec<&str> = included.iter().map(|map| map.version.as_str()).collect();
+
+        assert_eq!(versions, vec!["a", "b"]);
+    }
+
+    fn versions(maps: &[Beatmap]) -> Vec<String> {
+        maps.iter().map(|map| map.version.clone()).collect()
+    }
+
+    #[test]
+    fn shuffle_is_deterministic_for_a_seed() {
+        let maps: Vec<Beatmap> = (0..6).map(|i| stream(&i.to_string(), 4, 100.0)).collect();
+
+        let first = versions(&shuffle_maps(maps.clone(), 42));
+        let second = versions(&shuffle_maps(maps.clone(), 42));
+        assert_eq!(first, second);
+
+        let mut sorted = first;
+        sorted.sort();
+        assert_eq!(sorted, versions(&maps));
+    }
+
+    #[test]
+    fn hardest_map_comes_first() {
+        let maps = vec![stream("easy", 20, 400.0), stream("hard", 20, 50.0), stream("normal", 20, 150.0)];
+
+        let marathon = concat_hardest_first(maps, Some(1000.0));
+
+        assert!(marathon.version.starts_with("hard"));
+        assert_eq!(marathon.hit_objects.len(), 60);
+        assert_eq!(marathon.hit_objects[1].start_time - marathon.hit_objects[0].start_time, 50.0);
+        assert_eq!(marathon.hit_objects[21].start_time - marathon.hit_objects[20].start_time, 150.0);
+    }
+
+    #[test]
+    fn every_other_map_is_mirrored() {
+        let maps = vec![stream("a", 4, 100.0), stream("b", 4, 100.0), stream("c", 4, 100.0)];
+
+        let marathon = concat_alternating_mirror(maps, Some(500.0));
+
+        let columns: Vec<u32> = marathon.hit_objects.iter().map(|note| column(note, 4).unwrap()).collect();
+        assert_eq!(columns, vec![0, 1, 2, 3, 3, 2, 1, 0, 0, 1, 2, 3]);
+    }
+}
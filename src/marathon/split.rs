@@ -0,0 +1,142 @@
+use rosu_map::Beatmap;
+
+/// Splits a marathon back into separate beatmaps
+///
+/// # Arguments
+/// * `beatmap` - The merged beatmap
+/// * `boundaries` - Start time of every map after the first, in milliseconds
+///
+/// # Returns
+/// One beatmap per slice, rebased so each slice starts at its boundary = 0.
+/// Hold notes straddling a boundary stay in the slice holding their start, and
+/// the control points active at a boundary are carried into the next slice
+/// (at a negative time, which keeps the beat phase). Breaks, bookmarks and the
+/// preview point go to the slice they start in; a slice without a preview
+/// point has it unset.
+pub fn unmarathon(beatmap: &Beatmap, boundaries: &[f64]) -> Vec<Beatmap> {
+    let mut starts = vec![f64::NEG_INFINITY];
+    starts.extend_from_slice(boundaries);
+    starts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let count = starts.len();
+    let mut slices = Vec::with_capacity(count);
+
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(f64::INFINITY);
+        let offset = if start.is_finite() { start } else { 0.0 };
+        let mut slice = beatmap.clone();
+
+        slice.hit_objects = beatmap.hit_objects.iter()
+            .filter(|hit_object| hit_object.start_time >= start && hit_object.start_time < end)
+            .cloned()
+            .map(|mut hit_object| {
+                hit_object.start_time -= offset;
+                hit_object
+            })
+            .collect();
+
+        let points = &beatmap.control_points;
+        slice.control_points.timing_points =
+            slice_points(&points.timing_points, start, end, offset, |p| &mut p.time, |p| p.time);
+        slice.control_points.effect_points =
+            slice_points(&points.effect_points, start, end, offset, |p| &mut p.time, |p| p.time);
+        slice.control_points.difficulty_points =
+            slice_points(&points.difficulty_points, start, end, offset, |p| &mut p.time, |p| p.time);
+        slice.control_points.sample_points =
+            slice_points(&points.sample_points, start, end, offset, |p| &mut p.time, |p| p.time);
+
+        let inside = |time: f64| time >= start && time < end;
+
+        slice.breaks = beatmap.breaks.iter()
+            .filter(|break_period| inside(break_period.start_time))
+            .cloned()
+            .map(|mut break_period| {
+                break_period.start_time -= offset;
+                break_period.end_time -= offset;
+                break_period
+            })
+            .collect();
+
+        slice.bookmarks = beatmap.bookmarks.iter()
+            .filter(|&&bookmark| inside(bookmark as f64))
+            .map(|&bookmark| bookmark - offset.round() as i32)
+            .collect();
+
+        slice.preview_time = if beatmap.preview_time >= 0 && inside(beatmap.preview_time as f64) {
+            beatmap.preview_time - offset.round() as i32
+        } else {
+            -1
+        };
+
+        slice.version = format!("{} ({}/{})", beatmap.version, i + 1, count);
+        slices.push(slice);
+    }
+
+    slices
+}
+
+/// Points within `[start, end)` plus the one active at `start`, shifted by `-offset`
+fn slice_points<T: Clone>(
+    points: &[T],
+    start: f64,
+    end: f64,
+    offset: f64,
+    time_mut: fn(&mut T) -> &mut f64,
+    time: fn(&T) -> f64,
+) -> Vec<T> {
+    let active = points.iter()
+        .filter(|point| time(point) < start)
+        .last()
+        .filter(|_| !points.iter().any(|point| time(point) == start));
+
+    active.into_iter()
+        .chain(points.iter().filter(|point| time(point) >= start && time(point) < end))
+        .cloned()
+        .map(|mut point| {
+            *time_mut(&mut point) -= offset;
+            point
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::events::BreakPeriod;
+    use crate::marathon::marathon::concat_beatmaps;
+    use crate::mania::new_note;
+
+    fn map_4k(notes: usize) -> Beatmap {
+        Beatmap {
+            circle_size: 4.0,
+            hit_objects: (0..notes).map(|i| new_note(i as f64 * 1000.0, (i % 4) as u32, 4)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn split_restores_each_map() {
+        let mut first = map_4k(3);
+        first.breaks.push(BreakPeriod { start_time: 200.0, end_time: 800.0 });
+        first.bookmarks.push(500);
+        first.preview_time = 1000;
+        let second = map_4k(5);
+
+        let mut marathon = concat_beatmaps(vec![first, second], Some(1000.0));
+        // The second map starts at 3000ms
+        marathon.breaks.push(BreakPeriod { start_time: 4500.0, end_time: 5500.0 });
+        let slices = unmarathon(&marathon, &[3000.0]);
+
+        assert_eq!(slices.len(), 2);
+        assert_eq!(slices[0].hit_objects.len(), 3);
+        assert_eq!(slices[1].hit_objects.len(), 5);
+        assert_eq!(slices[1].hit_objects[0].start_time, 0.0);
+
+        assert_eq!(slices[0].breaks.len(), 1);
+        assert_eq!(slices[1].breaks, vec![BreakPeriod { start_time: 1500.0, end_time: 2500.0 }]);
+        assert_eq!(slices[0].bookmarks, vec![500]);
+        assert!(slices[1].bookmarks.is_empty());
+        assert_eq!(slices[0].preview_time, 1000);
+        assert_eq!(slices[1].preview_time, -1);
+    }
+}
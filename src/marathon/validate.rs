@@ -0,0 +1,231 @@
+use rosu_map::Beatmap;
+
+use super::marathon::get_beatmap_duration;
+use crate::mania::column::detect_mania_keymode;
+use crate::timing::{dominant_snap, timing_point_at};
+
+/// A problem preventing a set of beatmaps from being concatenated
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreflightIssue {
+    /// No beatmap was given
+    NoBeatmaps,
+    /// The beatmap at `index` is not an osu!mania map
+    NotMania { index: usize },
+    /// The beatmap at `index` has `found` keys while the first mania map has `expected`
+    KeyMismatch { index: usize, expected: u32, found: u32 },
+    /// The beatmap at `index` has no hit objects
+    Empty { index: usize },
+}
+
+/// Checks every input of a marathon at once
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps about to be concatenated
+///
+/// # Returns
+/// `Ok(())` if they can be concatenated, otherwise every issue found so they
+/// can all be fixed in one pass
+pub fn preflight(beatmaps: &[Beatmap]) -> Result<(), Vec<PreflightIssue>> {
+    if beatmaps.is_empty() {
+        return Err(vec![PreflightIssue::NoBeatmaps]);
+    }
+
+    let mut issues = Vec::new();
+    let mut expected = None;
+
+    for (index, beatmap) in beatmaps.iter().enumerate() {
+        match detect_mania_keymode(beatmap) {
+            None => issues.push(PreflightIssue::NotMania { index }),
+            Some(found) => match expected {
+                None => expected = Some(found),
+                Some(expected) if expected != found => {
+                    issues.push(PreflightIssue::KeyMismatch { index, expected, found });
+                }
+                Some(_) => {}
+            },
+        }
+
+        if beatmap.hit_objects.is_empty() {
+            issues.push(PreflightIssue::Empty { index });
+        }
+    }
+
+    if issues.is_empty() { Ok(()) } else { Err(issues) }
+}
+
+/// Checks that a marathon kept every object of its inputs
+///
+/// # Returns
+/// An error describing the mismatch if the result does not hold exactly the
+/// sum of the inputs' hit objects
+pub fn assert_conservation(inputs: &[Beatmap], result: &Beatmap) -> Result<(), String> {
+    assert_conservation_with_fills(inputs, result, 0)
+}
+
+/// Same as `assert_conservation`, for marathons where `fills` objects were
+/// deliberately added (gap fills, outros, ...)
+pub fn assert_conservation_with_fills(inputs: &[Beatmap], result: &Beatmap, fills: usize) -> Result<(), String> {
+    let expected = inputs.iter().map(|beatmap| beatmap.hit_objects.len()).sum::<usize>() + fills;
+    let found = result.hit_objects.len();
+
+    if found == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "Marathon has {} hit objects but its {} inputs and {} fills add up to {}",
+            found, inputs.len(), fills, expected
+        ))
+    }
+}
+
+/// Resolution of the search done by `optimal_gap`
+pub const GAP_SEARCH_STEP_MS: f64 = 1.0;
+
+/// Gap between maps that keeps the joins of a marathon closest to the beat
+///
+/// At each join the next map's first red line should fall on the beat grid of
+/// the previous map's last red line. Every gap from `min_gap_ms` to
+/// `max_gap_ms` is tried in steps of `GAP_SEARCH_STEP_MS`.
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps in marathon order
+/// * `min_gap_ms` - Shortest gap allowed
+/// * `max_gap_ms` - Longest gap allowed
+///
+/// # Returns
+/// The gap with the smallest total distance to the beat over all joins (the
+/// shortest one on ties), `min_gap_ms` if no join has timing on both sides
+pub fn optimal_gap(beatmaps: &[Beatmap], min_gap_ms: f64, max_gap_ms: f64) -> f64 {
+    // For each join, without the gap: distance from the previous map's last
+    // red line to the next map's first one, and the beat length in between
+    let joins: Vec<(f64, f64)> = beatmaps.windows(2)
+        .filter_map(|pair| {
+            let previous = timing_point_at(&pair[0], f64::INFINITY)?;
+            let next = pair[1].control_points.timing_points.first()?;
+            Some((get_beatmap_duration(&pair[0]) + next.time - previous.time, previous.beat_len))
+        })
+        .collect();
+
+    let drift = |gap: f64| -> f64 {
+        joins.iter()
+            .map(|&(distance, beat_len)| {
+                let beats = (distance + gap) / beat_len;
+                (beats - beats.round()).abs() * beat_len
+            })
+            .sum()
+    };
+
+    let steps = ((max_gap_ms - min_gap_ms) / GAP_SEARCH_STEP_MS).floor().max(0.0) as usize;
+    let mut best = (min_gap_ms, drift(min_gap_ms));
+
+    for step in 1..=steps {
+        let gap = min_gap_ms + step as f64 * GAP_SEARCH_STEP_MS;
+        let total = drift(gap);
+        if total < best.1 {
+            best = (gap, total);
+        }
+    }
+
+    best.0
+}
+
+/// Dominant snap of each beatmap, so mixed snapping can be warned about
+///
+/// Sources with different dominant snaps (1/4 next to 1/6) make a marathon
+/// feel disjointed.
+///
+/// # Arguments
+/// * `beatmaps` - The beatmaps about to be concatenated
+///
+/// # Returns
+/// The `dominant_snap` divisor of each beatmap, 0 where none was found
+pub fn snap_consistency(beatmaps: &[Beatmap]) -> Vec<u32> {
+    beatmaps.iter()
+        .map(|beatmap| dominant_snap(beatmap).unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::general::GameMode;
+    use crate::mania::new_note;
+    use crate::marathon::marathon::concat_beatmaps;
+    use rosu_map::section::timing_points::TimingPoint;
+
+    fn mania_map(keys: u32, notes: usize) -> Beatmap {
+        Beatmap {
+            mode: GameMode::Mania,
+            circle_size: keys as f32,
+            hit_objects: (0..notes).map(|i| new_note(i as f64 * 100.0, 0, keys)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn preflight_reports_every_issue() {
+        let beatmaps = vec![
+            mania_map(4, 4),
+            Beatmap::default(),
+            mania_map(7, 4),
+            mania_map(4, 0),
+        ];
+
+        assert_eq!(preflight(&beatmaps), Err(vec![
+            PreflightIssue::NotMania { index: 1 },
+            PreflightIssue::Empty { index: 1 },
+            PreflightIssue::KeyMismatch { index: 2, expected: 4, found: 7 },
+            PreflightIssue::Empty { index: 3 },
+        ]));
+        assert_eq!(preflight(&[]), Err(vec![PreflightIssue::NoBeatmaps]));
+        assert_eq!(preflight(&beatmaps[..1]), Ok(()));
+    }
+
+    #[test]
+    fn conservation_catches_dropped_notes() {
+        let inputs = vec![mania_map(4, 4), mania_map(4, 3)];
+        let mut result = concat_beatmaps(inputs.clone(), None);
+        assert_eq!(assert_conservation(&inputs, &result), Ok(()));
+
+        result.hit_objects.pop();
+        assert!(assert_conservation(&inputs, &result).is_err());
+        assert_eq!(assert_conservation_with_fills(&inputs[..1], &result, 2), Ok(()));
+    }
+
+    /// 120 BPM map with a red line at 0 and its last note at `last_note`
+    fn timed_map(last_note: f64) -> Beatmap {
+        let mut map = mania_map(4, 0);
+        map.control_points.timing_points.push(TimingPoint::new(
+            0.0,
+            500.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        map.hit_objects = vec![new_note(0.0, 0, 4), new_note(last_note, 1, 4)];
+        map
+    }
+
+    #[test]
+    fn optimal_gap_puts_every_join_on_the_beat() {
+        let beatmaps = vec![timed_map(7375.0), timed_map(7375.0), timed_map(7375.0)];
+
+        assert_eq!(optimal_gap(&beatmaps, 0.0, 400.0), 125.0);
+        assert_eq!(optimal_gap(&beatmaps, 200.0, 1000.0), 625.0);
+        assert_eq!(optimal_gap(&[timed_map(7375.0)], 50.0, 400.0), 50.0);
+    }
+
+    /// Map with a red line of 600ms beats and 24 notes `spacing_ms` apart
+    fn snapped_map(spacing_ms: f64) -> Beatmap {
+        let mut map = timed_map(0.0);
+        map.control_points.timing_points[0].beat_len = 600.0;
+        map.hit_objects = (0..24).map(|i| new_note(i as f64 * spacing_ms, i % 4, 4)).collect();
+        map
+    }
+
+    #[test]
+    fn snap_consistency_reports_each_dominant_snap() {
+        let beatmaps = vec![snapped_map(150.0), snapped_map(100.0), mania_map(4, 0)];
+
+        assert_eq!(snap_consistency(&beatmaps), vec![4, 6, 0]);
+    }
+}
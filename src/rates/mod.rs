@@ -1,3 +1,3 @@
 pub mod rates;
 
-pub use rates::rate;
\ No newline at end of file
+pub use rates::{apply_rate_with_audio, rate};
\ No newline at end of file
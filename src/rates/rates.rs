@@ -35,4 +35,32 @@ fn match_hit_object(hit_object: &mut HitObject, time_multiplier: f64) {
     if let HitObjectKind::Hold(hold) = &mut hit_object.kind {
         hold.duration *= time_multiplier;
     }
-}
\ No newline at end of file
+}
+
+/// Applies `rate` and points the map to the given (already rated) audio file
+///
+/// The crate only edits the .osu, so the caller is responsible for shipping
+/// `audio_filename` with the map.
+pub fn apply_rate_with_audio(map: &mut Beatmap, rate_value: f64, audio_filename: &str) {
+    rate(rate_value, map);
+    map.audio_file = audio_filename.to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audio_filename_and_version_are_set() {
+        let mut map = Beatmap {
+            audio_file: "audio.mp3".to_string(),
+            version: "Hard".to_string(),
+            ..Default::default()
+        };
+
+        apply_rate_with_audio(&mut map, 1.5, "audio_15x.ogg");
+
+        assert_eq!(map.audio_file, "audio_15x.ogg");
+        assert_eq!(map.version, "Hard x1.5");
+    }
+}
@@ -0,0 +1,60 @@
+/// Settings of a scrolling preview
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderConfig {
+    /// Base scroll speed in distance units per millisecond
+    pub scroll_speed: f64,
+    /// How far ahead of the current time notes are visible
+    pub lookahead_ms: f64,
+    /// Extra scroll multiplier of each column, missing columns use 1.0
+    ///
+    /// osu! has no per-lane scroll; this only affects previews.
+    pub column_sv: Vec<f64>,
+}
+
+impl RenderConfig {
+    pub fn new(scroll_speed: f64, lookahead_ms: f64) -> Self {
+        Self {
+            scroll_speed,
+            lookahead_ms,
+            column_sv: Vec::new(),
+        }
+    }
+
+    /// Scroll multiplier of `column`
+    pub fn column_multiplier(&self, column: u32) -> f64 {
+        self.column_sv.get(column as usize).copied().unwrap_or(1.0)
+    }
+}
+
+/// Makes the notes of `column` scroll `multiplier` times faster in previews
+pub fn set_column_sv(render_config: &mut RenderConfig, column: u32, multiplier: f64) {
+    let column = column as usize;
+    if render_config.column_sv.len() <= column {
+        render_config.column_sv.resize(column + 1, 1.0);
+    }
+    render_config.column_sv[column] = multiplier;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::Beatmap;
+    use crate::mania::new_note;
+    use crate::render::positions::render_positions_with;
+
+    #[test]
+    fn column_multiplier_scales_only_its_column() {
+        let map = Beatmap {
+            circle_size: 4.0,
+            hit_objects: vec![new_note(1000.0, 0, 4), new_note(1000.0, 2, 4)],
+            ..Default::default()
+        };
+        let mut config = RenderConfig::new(0.5, 2000.0);
+        set_column_sv(&mut config, 2, 2.0);
+
+        let notes = render_positions_with(&map, 0.0, &config);
+
+        assert_eq!(config.column_sv, vec![1.0, 1.0, 2.0]);
+        assert_eq!(notes.iter().map(|note| note.y).collect::<Vec<_>>(), vec![500.0, 1000.0]);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod config;
+pub mod positions;
+
+pub use config::{RenderConfig, set_column_sv};
+pub use positions::{RenderNote, render_positions, render_positions_with};
@@ -0,0 +1,120 @@
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::DifficultyPoint;
+
+use super::config::RenderConfig;
+use crate::mania::column::{column, key_count};
+use crate::mania::end_time;
+
+/// A note as seen by a scrolling renderer
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderNote {
+    /// Index into `hit_objects`
+    pub index: usize,
+    pub column: u32,
+    /// Distance above the judgement line of the head (negative once passed)
+    pub y: f64,
+    /// Distance above the judgement line of the tail, for holds
+    pub tail_y: Option<f64>,
+}
+
+/// Computes where each visible note is drawn at `at_time`
+///
+/// The vertical position is the scroll distance between `at_time` and the note,
+/// integrating the SV (difficulty point multipliers) over that span, so
+/// `y = (note_time - at_time) * scroll_speed` under constant 1.0x SV.
+///
+/// # Arguments
+/// * `at_time` - Current playback time in milliseconds
+/// * `scroll_speed` - Base scroll speed in distance units per millisecond
+/// * `lookahead_ms` - How far ahead of `at_time` notes are visible
+///
+/// # Returns
+/// Notes whose head is within the window or whose hold body is still on screen
+pub fn render_positions(beatmap: &Beatmap, at_time: f64, scroll_speed: f64, lookahead_ms: f64) -> Vec<RenderNote> {
+    render_positions_with(beatmap, at_time, &RenderConfig::new(scroll_speed, lookahead_ms))
+}
+
+/// Same as `render_positions`, with the settings of `config`
+///
+/// The distance of each note is additionally scaled by the multiplier of its
+/// column (see `set_column_sv`).
+pub fn render_positions_with(beatmap: &Beatmap, at_time: f64, config: &RenderConfig) -> Vec<RenderNote> {
+    let keys = key_count(beatmap);
+    let points = &beatmap.control_points.difficulty_points;
+    let window_end = at_time + config.lookahead_ms;
+
+    beatmap.hit_objects.iter()
+        .enumerate()
+        .filter_map(|(index, hit_object)| {
+            let column = column(hit_object, keys)?;
+            let end = end_time(hit_object);
+
+            if hit_object.start_time > window_end || end < at_time {
+                return None;
+            }
+            if end == hit_object.start_time && hit_object.start_time < at_time {
+                return None;
+            }
+
+            let speed = config.scroll_speed * config.column_multiplier(column);
+            let y = sv_distance(points, at_time, hit_object.start_time) * speed;
+            let tail_y = (end > hit_object.start_time)
+                .then(|| sv_distance(points, at_time, end) * speed);
+
+            Some(RenderNote { index, column, y, tail_y })
+        })
+        .collect()
+}
+
+/// SV multiplier active at `time`
+pub(crate) fn sv_at(points: &[DifficultyPoint], time: f64) -> f64 {
+    points.iter()
+        .take_while(|point| point.time <= time)
+        .last()
+        .map_or(DifficultyPoint::DEFAULT_SLIDER_VELOCITY, |point| point.slider_velocity)
+}
+
+/// Integral of the SV multiplier from `from` to `to` (negative if `to < from`)
+pub(crate) fn sv_distance(points: &[DifficultyPoint], from: f64, to: f64) -> f64 {
+    if to < from {
+        return -sv_distance(points, to, from);
+    }
+
+    let mut distance = 0.0;
+    let mut time = from;
+    let mut velocity = sv_at(points, from);
+
+    for point in points.iter().filter(|point| point.time > from && point.time < to) {
+        distance += (point.time - time) * velocity;
+        time = point.time;
+        velocity = point.slider_velocity;
+    }
+
+    distance + (to - time) * velocity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+    use crate::mania::new_note;
+
+    #[test]
+    fn note_one_beat_ahead_under_constant_sv() {
+        let beat_len = 500.0;
+        let scroll_speed = 0.8;
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.control_points.timing_points.push(TimingPoint::new(0.0, beat_len, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+        map.hit_objects.push(new_note(1000.0 + beat_len, 1, 4));
+
+        let notes = render_positions(&map, 1000.0, scroll_speed, 2000.0);
+
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0].column, 1);
+        assert!((notes[0].y - beat_len * scroll_speed).abs() < 1e-9);
+        assert_eq!(notes[0].tail_y, None);
+    }
+}
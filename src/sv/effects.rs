@@ -0,0 +1,104 @@
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::EffectPoint;
+
+/// Adds a kiai flash of `duration_ms` at each of the given times
+///
+/// Overlapping flashes are coalesced into a single kiai range. Effect points
+/// inside a range are replaced; at the end of a range the effect state that
+/// was active there before is restored.
+pub fn add_kiai_at(beatmap: &mut Beatmap, times: &[f64], duration_ms: f64) {
+    let mut starts = times.to_vec();
+    starts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut ranges: Vec<(f64, f64)> = Vec::new();
+    for start in starts {
+        let end = start + duration_ms;
+        match ranges.last_mut() {
+            Some(range) if start <= range.1 => range.1 = range.1.max(end),
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    let points = &mut beatmap.control_points.effect_points;
+    points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+    // Read every state from the original points, before any range is written
+    let states: Vec<(EffectPoint, EffectPoint)> = ranges.iter()
+        .map(|&(start, end)| (active_at(points, start), active_at(points, end)))
+        .collect();
+
+    for ((start, end), (at_start, at_end)) in ranges.into_iter().zip(states) {
+        points.retain(|point| point.time < start || point.time > end);
+        points.push(EffectPoint {
+            time: start,
+            kiai: true,
+            scroll_speed: at_start.scroll_speed,
+        });
+        points.push(EffectPoint { time: end, ..at_end });
+    }
+
+    points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+}
+
+/// Effect point active at `time`, the default one before the first point
+fn active_at(points: &[EffectPoint], time: f64) -> EffectPoint {
+    points.iter()
+        .rev()
+        .find(|point| point.time <= time)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Removes every effect (kiai) point
+pub fn remove_effects(beatmap: &mut Beatmap) {
+    beatmap.control_points.effect_points.clear();
+}
+
+/// Removes every effect (kiai) point except the first one
+///
+/// The map's initial effect state is unchanged.
+pub fn remove_effects_keep_first(beatmap: &mut Beatmap) {
+    beatmap.control_points.effect_points.truncate(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn effect(time: f64, kiai: bool) -> EffectPoint {
+        EffectPoint {
+            time,
+            kiai,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn two_flashes_restore_the_state_they_interrupt() {
+        let mut map = Beatmap::default();
+        map.control_points.effect_points = vec![effect(0.0, false), effect(3000.0, true)];
+
+        add_kiai_at(&mut map, &[1000.0, 5000.0], 500.0);
+
+        let points: Vec<(f64, bool)> = map.control_points.effect_points.iter()
+            .map(|point| (point.time, point.kiai))
+            .collect();
+        assert_eq!(
+            points,
+            vec![(0.0, false), (1000.0, true), (1500.0, false), (3000.0, true), (5000.0, true), (5500.0, true)]
+        );
+    }
+
+    #[test]
+    fn remove_effects_with_several_points() {
+        let mut map = Beatmap::default();
+        map.control_points.effect_points = vec![effect(0.0, false), effect(1000.0, true), effect(2000.0, false)];
+        let mut kept = map.clone();
+
+        remove_effects(&mut map);
+        remove_effects_keep_first(&mut kept);
+
+        assert!(map.control_points.effect_points.is_empty());
+        assert_eq!(kept.control_points.effect_points, vec![effect(0.0, false)]);
+    }
+}
@@ -0,0 +1,128 @@
+pub mod effects;
+pub mod theme;
+
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::DifficultyPoint;
+
+/// Lowest SV multiplier osu! accepts
+pub const MIN_SV: f64 = 0.1;
+/// Highest SV multiplier osu! accepts
+pub const MAX_SV: f64 = 10.0;
+
+/// Scales how far each SV multiplier deviates from 1.0
+///
+/// `new = 1.0 + (old - 1.0) * factor`, clamped to `MIN_SV..=MAX_SV`.
+/// A factor of 0 flattens every SV change, 2.0 exaggerates them.
+pub fn scale_sv(beatmap: &mut Beatmap, factor: f64) {
+    for point in beatmap.control_points.difficulty_points.iter_mut() {
+        let scaled = 1.0 + (point.slider_velocity - 1.0) * factor;
+        point.slider_velocity = scaled.clamp(MIN_SV, MAX_SV);
+    }
+}
+
+/// Resets every SV multiplier to 1.0
+///
+/// Difficulty points keep their time and other attributes, and effect points
+/// (kiai) are untouched.
+pub fn flatten_sv_keep_effects(beatmap: &mut Beatmap) {
+    for point in beatmap.control_points.difficulty_points.iter_mut() {
+        point.slider_velocity = DifficultyPoint::DEFAULT_SLIDER_VELOCITY;
+    }
+}
+
+/// Scroll distance between two times under BPM-based scroll, ignoring SV
+///
+/// Scroll moves one unit per beat, so the distance is the number of beats
+/// between `from` and `to` following every red line (the first one also
+/// applies before it). Negative if `to < from`; 0 without red lines.
+pub fn scroll_distance(beatmap: &Beatmap, from: f64, to: f64) -> f64 {
+    if to < from {
+        return -scroll_distance(beatmap, to, from);
+    }
+
+    let points = &beatmap.control_points.timing_points;
+    let mut distance = 0.0;
+
+    for (i, point) in points.iter().enumerate() {
+        let span_start = if i == 0 { f64::NEG_INFINITY } else { point.time };
+        let span_end = points.get(i + 1).map_or(f64::INFINITY, |next| next.time);
+        let overlap = to.min(span_end) - from.max(span_start);
+
+        if overlap > 0.0 {
+            distance += overlap / point.beat_len;
+        }
+    }
+
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::{EffectPoint, TimingPoint};
+
+    fn sv_map(velocities: &[f64]) -> Beatmap {
+        let mut map = Beatmap::default();
+        map.control_points.difficulty_points = velocities.iter()
+            .enumerate()
+            .map(|(i, &slider_velocity)| DifficultyPoint {
+                time: i as f64 * 1000.0,
+                slider_velocity,
+                generate_ticks: true,
+            })
+            .collect();
+        map
+    }
+
+    fn velocities(map: &Beatmap) -> Vec<f64> {
+        map.control_points.difficulty_points.iter().map(|point| point.slider_velocity).collect()
+    }
+
+    #[test]
+    fn scale_sv_doubles_the_deviation() {
+        let mut map = sv_map(&[1.5, 1.0, 0.75]);
+
+        scale_sv(&mut map, 2.0);
+
+        assert_eq!(velocities(&map), vec![2.0, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn scale_sv_clamps_to_the_accepted_range() {
+        let mut map = sv_map(&[6.0, 0.5]);
+
+        scale_sv(&mut map, 3.0);
+
+        assert_eq!(velocities(&map), vec![MAX_SV, MIN_SV]);
+    }
+
+    #[test]
+    fn scroll_distance_follows_each_red_line() {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points = vec![
+            TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(2000.0, 250.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+        ];
+
+        assert_eq!(scroll_distance(&map, 1000.0, 3000.0), 6.0);
+        assert_eq!(scroll_distance(&map, 3000.0, 1000.0), -6.0);
+        assert_eq!(scroll_distance(&map, -1000.0, 0.0), 2.0);
+        assert_eq!(scroll_distance(&Beatmap::default(), 0.0, 1000.0), 0.0);
+    }
+
+    #[test]
+    fn flatten_keeps_kiai() {
+        let mut map = sv_map(&[1.5, 0.5, 2.0]);
+        map.control_points.effect_points.push(EffectPoint {
+            time: 1000.0,
+            kiai: true,
+            ..Default::default()
+        });
+        let effects = map.control_points.effect_points.clone();
+
+        flatten_sv_keep_effects(&mut map);
+
+        assert_eq!(velocities(&map), vec![1.0, 1.0, 1.0]);
+        assert_eq!(map.control_points.effect_points, effects);
+    }
+}
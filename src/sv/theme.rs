@@ -0,0 +1,124 @@
+use std::f64::consts::TAU;
+
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::DifficultyPoint;
+
+use super::{MAX_SV, MIN_SV};
+
+/// Spacing of the points generated for ramps and pulses
+pub const THEME_STEP_MS: f64 = 50.0;
+
+/// Shape of the SV over a section
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SvShape {
+    /// A single multiplier for the whole section
+    Constant(f64),
+    /// Linear change from one multiplier to another
+    Ramp { from: f64, to: f64 },
+    /// Oscillation around 1.0x, `period` in milliseconds
+    Pulse { amplitude: f64, period: f64 },
+}
+
+impl SvShape {
+    /// Multiplier `elapsed` ms into a section lasting `length` ms
+    pub fn multiplier(self, elapsed: f64, length: f64) -> f64 {
+        let sv = match self {
+            SvShape::Constant(sv) => sv,
+            SvShape::Ramp { from, to } => from + (to - from) * (elapsed / length).clamp(0.0, 1.0),
+            SvShape::Pulse { amplitude, period } => 1.0 + amplitude * (TAU * elapsed / period).sin(),
+        };
+
+        sv.clamp(MIN_SV, MAX_SV)
+    }
+}
+
+/// Fills each `(start, end, shape)` section with generated SV
+///
+/// The SV points of a section are replaced: constants get one point, ramps and
+/// pulses one every `THEME_STEP_MS`. At the end of a section the SV that was
+/// active there before is restored.
+pub fn apply_sv_theme(beatmap: &mut Beatmap, sections: &[(f64, f64, SvShape)]) {
+    for &(start, end, shape) in sections {
+        let points = &mut beatmap.control_points.difficulty_points;
+        let restore = points.iter()
+            .rev()
+            .find(|point| point.time <= end)
+            .map_or(DifficultyPoint::DEFAULT_SLIDER_VELOCITY, |point| point.slider_velocity);
+
+        points.retain(|point| point.time < start || point.time > end);
+
+        let length = end - start;
+        let times: Vec<f64> = match shape {
+            SvShape::Constant(_) => vec![start],
+            _ => (0..)
+                .map(|i| start + i as f64 * THEME_STEP_MS)
+                .take_while(|&time| time < end)
+                .collect(),
+        };
+
+        for time in times {
+            points.push(DifficultyPoint {
+                time,
+                slider_velocity: shape.multiplier(time - start, length),
+                generate_ticks: true,
+            });
+        }
+        points.push(DifficultyPoint {
+            time: end,
+            slider_velocity: restore,
+            generate_ticks: true,
+        });
+
+        points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(time, slider_velocity)` of every SV point
+    fn points(map: &Beatmap) -> Vec<(f64, f64)> {
+        map.control_points.difficulty_points.iter()
+            .map(|point| (point.time, point.slider_velocity))
+            .collect()
+    }
+
+    #[test]
+    fn constant_restores_the_previous_sv() {
+        let mut map = Beatmap::default();
+        map.control_points.difficulty_points.push(DifficultyPoint {
+            time: 0.0,
+            slider_velocity: 0.8,
+            generate_ticks: true,
+        });
+
+        apply_sv_theme(&mut map, &[(1000.0, 2000.0, SvShape::Constant(1.5))]);
+
+        assert_eq!(points(&map), vec![(0.0, 0.8), (1000.0, 1.5), (2000.0, 0.8)]);
+    }
+
+    #[test]
+    fn ramp_is_linear() {
+        let mut map = Beatmap::default();
+
+        apply_sv_theme(&mut map, &[(0.0, 200.0, SvShape::Ramp { from: 0.5, to: 1.5 })]);
+
+        assert_eq!(points(&map), vec![(0.0, 0.5), (50.0, 0.75), (100.0, 1.0), (150.0, 1.25), (200.0, 1.0)]);
+    }
+
+    #[test]
+    fn pulse_oscillates_around_one() {
+        let mut map = Beatmap::default();
+
+        apply_sv_theme(&mut map, &[(0.0, 200.0, SvShape::Pulse { amplitude: 0.5, period: 200.0 })]);
+
+        let expected = [(0.0, 1.0), (50.0, 1.5), (100.0, 1.0), (150.0, 0.5), (200.0, 1.0)];
+        let found = points(&map);
+        assert_eq!(found.len(), expected.len());
+        for ((time, sv), (expected_time, expected_sv)) in found.into_iter().zip(expected) {
+            assert_eq!(time, expected_time);
+            assert!((sv - expected_sv).abs() < 1e-9, "{} at {}", sv, time);
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use rosu_map::Beatmap;
+
+/// A span of the map played at a single BPM
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BpmSegment {
+    pub start: f64,
+    pub end: f64,
+    pub bpm: f64,
+}
+
+/// Converts a beat length in milliseconds to BPM
+pub fn bpm_of(beat_len: f64) -> f64 {
+    60_000.0 / beat_len
+}
+
+/// Splits the map into BPM segments, one per red line
+///
+/// Consecutive red lines with the same BPM are merged. The last segment ends
+/// at `end_time`.
+pub fn bpm_segments(beatmap: &Beatmap, end_time: f64) -> Vec<BpmSegment> {
+    let mut segments: Vec<BpmSegment> = Vec::new();
+
+    for point in &beatmap.control_points.timing_points {
+        let bpm = bpm_of(point.beat_len);
+
+        if let Some(last) = segments.last_mut() {
+            if (last.bpm - bpm).abs() < 1e-6 {
+                continue;
+            }
+            last.end = point.time;
+        }
+
+        segments.push(BpmSegment {
+            start: point.time,
+            end: end_time,
+            bpm,
+        });
+    }
+
+    if let Some(last) = segments.last_mut() {
+        last.end = last.end.max(last.start);
+    }
+
+    segments
+}
@@ -0,0 +1,88 @@
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::TimingPoint;
+
+/// Moves the first red line so the first note falls on one of its beats
+///
+/// The BPM is unchanged; the red line moves forward by less than one beat
+/// (or back onto the first note if it started after it), so it never ends up
+/// after the first note.
+pub fn anchor_first_timing(beatmap: &mut Beatmap) {
+    let Some(first_note) = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+    else {
+        return;
+    };
+    let Some(point) = beatmap.control_points.timing_points.first_mut() else {
+        return;
+    };
+
+    let beats = ((first_note - point.time) / point.beat_len).floor().max(0.0);
+    point.time = first_note - beats * point.beat_len;
+}
+
+/// Merges red lines closer than `tolerance_ms` to each other
+///
+/// Each cluster of coincident red lines becomes one red line at the time of
+/// the earliest, carrying the values (BPM, meter) of the latest, which is the
+/// one osu! would apply.
+pub fn merge_coincident_timing(beatmap: &mut Beatmap, tolerance_ms: f64) {
+    let points = &mut beatmap.control_points.timing_points;
+    points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+    let mut merged: Vec<TimingPoint> = Vec::with_capacity(points.len());
+    let mut cluster_start = f64::NEG_INFINITY;
+
+    for point in points.drain(..) {
+        match merged.last_mut() {
+            Some(last) if point.time - cluster_start <= tolerance_ms => {
+                *last = TimingPoint { time: cluster_start, ..point };
+            }
+            _ => {
+                cluster_start = point.time;
+                merged.push(point);
+            }
+        }
+    }
+
+    *points = merged;
+}
+
+#[cfg(all(test, feature = "mania"))]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    #[test]
+    fn first_note_lands_on_a_beat() {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points.push(TimingPoint::new(30.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+        map.hit_objects.push(new_note(1270.0, 0, 4));
+        map.hit_objects.push(new_note(1520.0, 0, 4));
+
+        anchor_first_timing(&mut map);
+
+        let point = &map.control_points.timing_points[0];
+        assert_eq!(point.beat_len, 500.0);
+        assert!(point.time <= 1270.0);
+        let beats = (1270.0 - point.time) / point.beat_len;
+        assert!((beats - beats.round()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn red_lines_half_a_millisecond_apart_merge() {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points = vec![
+            TimingPoint::new(1000.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(1000.5, 400.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(5000.0, 300.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+        ];
+
+        merge_coincident_timing(&mut map, 1.0);
+
+        let points: Vec<(f64, f64)> = map.control_points.timing_points.iter()
+            .map(|point| (point.time, point.beat_len))
+            .collect();
+        assert_eq!(points, vec![(1000.0, 400.0), (5000.0, 300.0)]);
+    }
+}
@@ -0,0 +1,157 @@
+use rosu_map::Beatmap;
+
+use super::bpm::bpm_of;
+
+/// Notes closer than this are treated as one chord when measuring intervals
+const CHORD_TOLERANCE_MS: f64 = 2.0;
+
+/// Best-effort BPM guess from the note rhythm, ignoring the timing points
+///
+/// Takes the most common interval between consecutive note times, assumes it
+/// is a 1/4 beat (the usual stream snap), and folds the result into the
+/// 60-300 BPM range. This is approximate: a map dominated by 1/2 or 1/3 notes
+/// will be reported at a related tempo.
+pub fn estimate_bpm(beatmap: &Beatmap) -> Option<f64> {
+    let mut times: Vec<f64> = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .collect();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    times.dedup_by(|b, a| *b - *a <= CHORD_TOLERANCE_MS);
+
+    let intervals: Vec<f64> = times.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    if intervals.is_empty() {
+        return None;
+    }
+
+    let mut buckets: Vec<(i64, usize)> = Vec::new();
+    for interval in &intervals {
+        let bucket = interval.round() as i64;
+        match buckets.iter_mut().find(|(b, _)| *b == bucket) {
+            Some((_, count)) => *count += 1,
+            None => buckets.push((bucket, 1)),
+        }
+    }
+    let (mode, _) = buckets.iter().max_by_key(|(_, count)| *count)?;
+
+    let close: Vec<f64> = intervals.iter()
+        .copied()
+        .filter(|interval| (interval - *mode as f64).abs() <= 1.0)
+        .collect();
+    let interval = close.iter().sum::<f64>() / close.len() as f64;
+
+    let mut bpm = bpm_of(interval * 4.0);
+    while bpm > 300.0 {
+        bpm /= 2.0;
+    }
+    while bpm < 60.0 {
+        bpm *= 2.0;
+    }
+
+    Some(bpm)
+}
+
+/// Division of the beat used as the reference grid by `grid_alignment`
+pub const ALIGNMENT_DIVISION: u32 = 4;
+
+/// Average distance of the notes to a reference grid, in milliseconds
+///
+/// The grid has a line every 1/`ALIGNMENT_DIVISION` beat at `grid_bpm`,
+/// going through `grid_offset`. A map timed to the wrong offset shows up as
+/// a value close to that offset error instead of near 0. Returns 0 for maps
+/// without notes.
+pub fn grid_alignment(beatmap: &Beatmap, grid_bpm: f64, grid_offset: f64) -> f64 {
+    if beatmap.hit_objects.is_empty() {
+        return 0.0;
+    }
+
+    let step = 60_000.0 / grid_bpm / ALIGNMENT_DIVISION as f64;
+    let total: f64 = beatmap.hit_objects.iter()
+        .map(|hit_object| {
+            let steps = (hit_object.start_time - grid_offset) / step;
+            (steps - steps.round()).abs() * step
+        })
+        .sum();
+
+    total / beatmap.hit_objects.len() as f64
+}
+
+/// Relative error allowed on the 2x ratio by `suggest_bpm_correction`
+pub const BPM_RATIO_TOLERANCE: f64 = 0.05;
+
+/// Suggests the corrected BPM of a map timed at double or half its tempo
+///
+/// Compares the BPM of the first red line to `estimate_bpm`. Returns the
+/// halved or doubled BPM when they differ by about 2x, `None` when they
+/// agree, differ by another ratio, or either is unknown.
+pub fn suggest_bpm_correction(beatmap: &Beatmap) -> Option<f64> {
+    let stated = bpm_of(beatmap.control_points.timing_points.first()?.beat_len);
+    let estimated = estimate_bpm(beatmap)?;
+    let ratio = stated / estimated;
+
+    if (ratio / 2.0 - 1.0).abs() <= BPM_RATIO_TOLERANCE {
+        Some(stated / 2.0)
+    } else if (ratio * 2.0 - 1.0).abs() <= BPM_RATIO_TOLERANCE {
+        Some(stated * 2.0)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::hit_objects::{HitObject, HitObjectCircle, HitObjectKind};
+    use rosu_map::util::Pos;
+    use rosu_map::section::timing_points::TimingPoint;
+
+    fn circles(times: impl Iterator<Item = f64>) -> Beatmap {
+        Beatmap {
+            hit_objects: times
+                .map(|time| HitObject {
+                    start_time: time,
+                    kind: HitObjectKind::Circle(HitObjectCircle {
+                        pos: Pos { x: 64.0, y: 0.0 },
+                        new_combo: false,
+                        combo_offset: 0,
+                    }),
+                    samples: Vec::new(),
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimates_150_bpm_from_a_quarter_grid() {
+        // 1/4 at 150 BPM is 100ms
+        let map = circles((0..64).map(|i| 1000.0 + i as f64 * 100.0));
+
+        let bpm = estimate_bpm(&map).unwrap();
+        assert!((bpm - 150.0).abs() < 0.01, "{bpm}");
+    }
+
+    #[test]
+    fn map_15ms_late_is_15ms_off_the_grid() {
+        let map = circles((0..32).map(|i| 15.0 + i as f64 * 125.0));
+
+        assert!((grid_alignment(&map, 120.0, 0.0) - 15.0).abs() < 1e-9);
+        assert!(grid_alignment(&map, 120.0, 15.0).abs() < 1e-9);
+        assert_eq!(grid_alignment(&Beatmap::default(), 120.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn double_time_bpm_is_halved() {
+        let mut map = circles((0..64).map(|i| 1000.0 + i as f64 * 100.0));
+        map.control_points.timing_points.push(TimingPoint::new(
+            1000.0,
+            200.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        let correction = suggest_bpm_correction(&map).unwrap();
+        assert!((correction - 150.0).abs() < 0.01, "{correction}");
+
+        map.control_points.timing_points[0].beat_len = 400.0;
+        assert_eq!(suggest_bpm_correction(&map), None);
+    }
+}
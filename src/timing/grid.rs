@@ -0,0 +1,57 @@
+use rosu_map::Beatmap;
+
+/// Margin keeping grid lines that land on a boundary despite float error
+const EPSILON: f64 = 1e-6;
+
+/// Every 1/`division` snap time in `[start, end)`
+///
+/// Each span uses the beat length of the red line governing it, and the grid
+/// restarts at every red line. Before the first red line, its grid is
+/// extended backwards.
+pub fn beat_grid(beatmap: &Beatmap, start: f64, end: f64, division: u32) -> Vec<f64> {
+    let points = &beatmap.control_points.timing_points;
+    let mut grid = Vec::new();
+
+    for (i, point) in points.iter().enumerate() {
+        let span_start = if i == 0 { f64::NEG_INFINITY } else { point.time };
+        let span_end = points.get(i + 1).map_or(f64::INFINITY, |next| next.time);
+        let from = start.max(span_start);
+        let to = end.min(span_end);
+
+        if from >= to {
+            continue;
+        }
+
+        let step = point.beat_len / division as f64;
+        let mut k = ((from - point.time) / step - EPSILON).ceil();
+        loop {
+            let time = point.time + k * step;
+            if time >= to - EPSILON {
+                break;
+            }
+            grid.push(time);
+            k += 1.0;
+        }
+    }
+
+    grid
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+
+    #[test]
+    fn quarter_grid_over_one_bar() {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points.push(TimingPoint::new(100.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+
+        let grid = beat_grid(&map, 100.0, 2100.0, 4);
+
+        assert_eq!(grid.len(), 16);
+        assert_eq!(grid[0], 100.0);
+        assert_eq!(grid[15], 1975.0);
+        assert!(grid.windows(2).all(|pair| (pair[1] - pair[0] - 125.0).abs() < 1e-9));
+    }
+}
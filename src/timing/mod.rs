@@ -0,0 +1,155 @@
+pub mod bpm;
+pub mod cleanup;
+pub mod estimate;
+pub mod grid;
+pub mod offset;
+pub mod snap;
+pub mod trim;
+
+pub use bpm::{BpmSegment, bpm_segments};
+pub use estimate::estimate_bpm;
+pub use grid::beat_grid;
+pub use offset::{shift_by_beats, shift_ms};
+pub use snap::{dominant_snap, find_unsnapped, quantize};
+pub use trim::trim_to_length;
+
+use rosu_map::Beatmap;
+use rosu_map::section::timing_points::TimingPoint;
+
+/// Uninherited (red) timing point governing the given time
+///
+/// Only `control_points.timing_points` is considered: inherited (green) lines
+/// are stored as difficulty/effect points and never change the beat length.
+/// Times before the first red line use the first one.
+pub fn timing_point_at(beatmap: &Beatmap, time: f64) -> Option<&TimingPoint> {
+    let points = &beatmap.control_points.timing_points;
+
+    points.iter()
+        .take_while(|point| point.time <= time)
+        .last()
+        .or(points.first())
+}
+
+/// Replaces the red lines with the given `(time, bpm)` list
+///
+/// New red lines use 4/4. Green lines (SV and effects) are stored separately
+/// and are left untouched.
+pub fn set_timing(beatmap: &mut Beatmap, points: &[(f64, f64)]) {
+    let mut timing_points: Vec<TimingPoint> = points.iter()
+        .map(|&(time, bpm)| {
+            TimingPoint::new(time, 60_000.0 / bpm, false, TimingPoint::DEFAULT_TIME_SIGNATURE)
+        })
+        .collect();
+    timing_points.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap());
+
+    beatmap.control_points.timing_points = timing_points;
+}
+
+/// `(time, meter)` of the red lines that change the time signature
+///
+/// The first red line is always included, as it sets the initial meter.
+pub fn meter_changes(beatmap: &Beatmap) -> Vec<(f64, u32)> {
+    let mut changes: Vec<(f64, u32)> = Vec::new();
+
+    for point in &beatmap.control_points.timing_points {
+        let meter = point.time_signature.numerator.get();
+
+        if changes.last().is_none_or(|&(_, last)| last != meter) {
+            changes.push((point.time, meter));
+        }
+    }
+
+    changes
+}
+
+/// Replaces the red lines of `target` with those of `master`
+///
+/// Both maps are assumed to use the same audio with the same offset. Notes
+/// and green lines of `target` are left in place; run `quantize` afterwards
+/// to move the notes onto the new grid.
+pub fn apply_master_timing(target: &mut Beatmap, master: &Beatmap) {
+    target.control_points.timing_points = master.control_points.timing_points.clone();
+}
+
+#[cfg(all(test, feature = "mania"))]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::{DifficultyPoint, EffectPoint, TimeSignature};
+    use crate::mania::new_note;
+
+    #[test]
+    fn set_timing_replaces_red_lines_and_keeps_green_lines() {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points.push(TimingPoint::new(
+            0.0,
+            1000.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        map.control_points.difficulty_points.push(DifficultyPoint {
+            time: 500.0,
+            slider_velocity: 1.5,
+            generate_ticks: true,
+        });
+        map.control_points.effect_points.push(EffectPoint {
+            time: 1000.0,
+            kiai: true,
+            ..Default::default()
+        });
+        let green_lines = (
+            map.control_points.difficulty_points.clone(),
+            map.control_points.effect_points.clone(),
+        );
+
+        set_timing(&mut map, &[(2000.0, 240.0), (0.0, 120.0)]);
+
+        let red_lines: Vec<(f64, f64)> = map.control_points.timing_points.iter()
+            .map(|point| (point.time, point.beat_len))
+            .collect();
+        assert_eq!(red_lines, vec![(0.0, 500.0), (2000.0, 250.0)]);
+        assert_eq!(
+            (map.control_points.difficulty_points, map.control_points.effect_points),
+            green_lines
+        );
+    }
+
+    #[test]
+    fn meter_changes_reports_each_switch() {
+        let three_four = TimeSignature::new(3).unwrap();
+        let mut map = Beatmap::default();
+        map.control_points.timing_points = vec![
+            TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(2000.0, 400.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(4000.0, 400.0, false, three_four),
+            TimingPoint::new(5200.0, 500.0, false, three_four),
+        ];
+
+        assert_eq!(meter_changes(&map), vec![(0.0, 4), (4000.0, 3)]);
+    }
+
+    #[test]
+    fn master_timing_replaces_red_lines_only() {
+        let mut master = Beatmap::default();
+        master.control_points.timing_points = vec![
+            TimingPoint::new(120.0, 400.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(8120.0, 375.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+        ];
+        let mut target = Beatmap {
+            circle_size: 4.0,
+            hit_objects: vec![new_note(100.0, 0, 4), new_note(510.0, 3, 4)],
+            ..Default::default()
+        };
+        target.control_points.timing_points.push(TimingPoint::new(
+            100.0,
+            410.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+        let notes = target.hit_objects.clone();
+
+        apply_master_timing(&mut target, &master);
+
+        assert_eq!(target.control_points.timing_points, master.control_points.timing_points);
+        assert_eq!(target.hit_objects, notes);
+    }
+}
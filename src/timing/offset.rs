@@ -0,0 +1,210 @@
+use rosu_map::Beatmap;
+
+use super::timing_point_at;
+
+/// Shifts the whole map by `offset_ms` (negative moves it earlier)
+///
+/// Moves hit objects, every control point, breaks, bookmarks and the preview
+/// point (unless it is unset).
+pub fn shift_ms(beatmap: &mut Beatmap, offset_ms: f64) {
+    for hit_object in beatmap.hit_objects.iter_mut() {
+        hit_object.start_time += offset_ms;
+    }
+
+    for timing_point in beatmap.control_points.timing_points.iter_mut() {
+        timing_point.time += offset_ms;
+    }
+
+    for effect_point in beatmap.control_points.effect_points.iter_mut() {
+        effect_point.time += offset_ms;
+    }
+
+    for difficulty_point in beatmap.control_points.difficulty_points.iter_mut() {
+        difficulty_point.time += offset_ms;
+    }
+
+    for sample_point in beatmap.control_points.sample_points.iter_mut() {
+        sample_point.time += offset_ms;
+    }
+
+    for break_period in beatmap.breaks.iter_mut() {
+        break_period.start_time += offset_ms;
+        break_period.end_time += offset_ms;
+    }
+
+    for bookmark in beatmap.bookmarks.iter_mut() {
+        *bookmark += offset_ms.round() as i32;
+    }
+
+    if beatmap.preview_time >= 0 {
+        beatmap.preview_time += offset_ms.round() as i32;
+    }
+}
+
+/// Shifts the whole map by `beats` beats
+///
+/// The beat length is taken from the first red line, so on maps with BPM
+/// changes the shift is only musically exact for that first section.
+pub fn shift_by_beats(beatmap: &mut Beatmap, beats: f64) {
+    let Some(beat_len) = beatmap.control_points.timing_points.first().map(|point| point.beat_len) else {
+        return;
+    };
+
+    shift_ms(beatmap, beats * beat_len);
+}
+
+/// Puts the first note on the first beat of a measure
+///
+/// Rather than moving the notes (which would break audio sync), the red line
+/// governing the first note (see `timing_point_at`) moves by whole beats until
+/// a bar starts at the first note, so the beat grid is unchanged and only the
+/// bar lines move. The meter comes from that red line. Notes off the beat use
+/// the nearest beat.
+///
+/// The red line moves forward, or back a bar from there if that would take it
+/// past the next red line. It never crosses another red line: when neither
+/// move fits, the rest of the map is shifted back onto the previous downbeat
+/// instead, leaving the red lines in place.
+pub fn align_to_bar(beatmap: &mut Beatmap) {
+    let Some(first_note) = beatmap.hit_objects.iter()
+        .map(|hit_object| hit_object.start_time)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+    else {
+        return;
+    };
+    let Some(point) = timing_point_at(beatmap, first_note) else {
+        return;
+    };
+
+    let points = &beatmap.control_points.timing_points;
+    let index = points.iter().position(|p| std::ptr::eq(p, point)).unwrap();
+    let previous = index.checked_sub(1).map_or(f64::NEG_INFINITY, |i| points[i].time);
+    let next = points.get(index + 1).map_or(f64::INFINITY, |p| p.time);
+
+    let meter = point.time_signature.numerator.get() as f64;
+    let beats = ((first_note - point.time) / point.beat_len).round();
+    let into_bar = beats.rem_euclid(meter);
+    if into_bar == 0.0 {
+        return;
+    }
+
+    let forward = point.time + into_bar * point.beat_len;
+    let backward = forward - meter * point.beat_len;
+    let downbeat = point.time + (beats - into_bar) * point.beat_len;
+
+    if forward < next {
+        beatmap.control_points.timing_points[index].time = forward;
+    } else if backward > previous {
+        beatmap.control_points.timing_points[index].time = backward;
+    } else {
+        let timing_points = beatmap.control_points.timing_points.clone();
+        shift_ms(beatmap, downbeat - first_note);
+        beatmap.control_points.timing_points = timing_points;
+    }
+}
+
+#[cfg(all(test, feature = "mania"))]
+mod tests {
+    use super::*;
+    use rosu_map::section::events::BreakPeriod;
+    use rosu_map::section::timing_points::{DifficultyPoint, TimingPoint};
+    use crate::mania::new_note;
+
+    /// 120 BPM map with a red line at `timing_time` and one note per time
+    fn map_120(timing_time: f64, notes: &[f64]) -> Beatmap {
+        let mut map = Beatmap {
+            hit_objects: notes.iter().map(|&time| new_note(time, 0, 4)).collect(),
+            ..Default::default()
+        };
+        map.control_points.timing_points.push(TimingPoint::new(timing_time, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+        map
+    }
+
+    #[test]
+    fn four_beats_at_120_bpm_is_two_seconds() {
+        let mut map = map_120(100.0, &[100.0, 600.0]);
+        map.control_points.difficulty_points.push(DifficultyPoint {
+            time: 600.0,
+            slider_velocity: 1.5,
+            generate_ticks: true,
+        });
+        map.breaks.push(BreakPeriod { start_time: 700.0, end_time: 900.0 });
+        map.bookmarks.push(600);
+        map.preview_time = 100;
+
+        shift_by_beats(&mut map, 4.0);
+
+        let times: Vec<f64> = map.hit_objects.iter().map(|h| h.start_time).collect();
+        assert_eq!(times, vec![2100.0, 2600.0]);
+        assert_eq!(map.control_points.timing_points[0].time, 2100.0);
+        assert_eq!(map.control_points.difficulty_points[0].time, 2600.0);
+        assert_eq!(map.breaks[0], BreakPeriod { start_time: 2700.0, end_time: 2900.0 });
+        assert_eq!(map.bookmarks, vec![2600]);
+        assert_eq!(map.preview_time, 2100);
+    }
+
+    #[test]
+    fn align_to_bar_moves_the_red_line_to_the_first_note() {
+        let mut map = map_120(0.0, &[1500.0, 2000.0]);
+        align_to_bar(&mut map);
+        assert_eq!(map.control_points.timing_points[0].time, 1500.0);
+        assert_eq!(map.hit_objects[0].start_time, 1500.0);
+
+        let mut map = map_120(0.0, &[2510.0]);
+        align_to_bar(&mut map);
+        assert_eq!(map.control_points.timing_points[0].time, 500.0);
+    }
+
+    #[test]
+    fn align_to_bar_moves_the_red_line_governing_the_first_note() {
+        let mut map = map_120(0.0, &[1800.0, 2200.0]);
+        map.control_points.timing_points.push(TimingPoint::new(
+            1000.0,
+            400.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+
+        align_to_bar(&mut map);
+
+        let times: Vec<f64> = map.control_points.timing_points.iter().map(|p| p.time).collect();
+        assert_eq!(times, vec![0.0, 1800.0]);
+        assert_eq!(map.hit_objects[0].start_time, 1800.0);
+    }
+
+    #[test]
+    fn align_to_bar_never_moves_a_red_line_past_the_next_one() {
+        // Moving the first red line forward to 1500 would cross the one at 1200
+        let mut map = map_120(1000.0, &[500.0]);
+        map.control_points.timing_points.push(TimingPoint::new(
+            1200.0,
+            400.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+
+        align_to_bar(&mut map);
+
+        let times: Vec<f64> = map.control_points.timing_points.iter().map(|p| p.time).collect();
+        assert_eq!(times, vec![500.0, 1200.0]);
+
+        // The red line at 1000 can go neither forward to 1500 nor back past 0,
+        // so the notes move back onto its downbeat instead
+        let mut map = map_120(0.0, &[1400.0, 1450.0]);
+        for (time, beat_len) in [(1000.0, 500.0), (1500.0, 400.0)] {
+            map.control_points.timing_points.push(TimingPoint::new(
+                time,
+                beat_len,
+                false,
+                TimingPoint::DEFAULT_TIME_SIGNATURE,
+            ));
+        }
+
+        align_to_bar(&mut map);
+
+        let times: Vec<f64> = map.control_points.timing_points.iter().map(|p| p.time).collect();
+        assert_eq!(times, vec![0.0, 1000.0, 1500.0]);
+        let notes: Vec<f64> = map.hit_objects.iter().map(|h| h.start_time).collect();
+        assert_eq!(notes, vec![1000.0, 1050.0]);
+    }
+}
@@ -0,0 +1,115 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use super::grid::beat_grid;
+use super::timing_point_at;
+
+/// Snaps a time to the nearest 1/`division` beat of the governing red line
+pub fn snap_time(beatmap: &Beatmap, time: f64, division: u32) -> f64 {
+    let Some(point) = timing_point_at(beatmap, time) else {
+        return time;
+    };
+    let step = point.beat_len / division as f64;
+
+    beat_grid(beatmap, time - step, time + step, division)
+        .into_iter()
+        .min_by(|a, b| (a - time).abs().partial_cmp(&(b - time).abs()).unwrap())
+        .unwrap_or(time)
+}
+
+/// Snaps every note (and hold end) to the 1/`division` grid
+pub fn quantize(beatmap: &mut Beatmap, division: u32) {
+    let snapped: Vec<(f64, Option<f64>)> = beatmap.hit_objects.iter()
+        .map(|hit_object| {
+            let start = snap_time(beatmap, hit_object.start_time, division);
+            let end = match hit_object.kind {
+                HitObjectKind::Hold(ref hold) => {
+                    Some(snap_time(beatmap, hit_object.start_time + hold.duration, division))
+                }
+                _ => None,
+            };
+            (start, end)
+        })
+        .collect();
+
+    for (hit_object, (start, end)) in beatmap.hit_objects.iter_mut().zip(snapped) {
+        hit_object.start_time = start;
+        if let (HitObjectKind::Hold(hold), Some(end)) = (&mut hit_object.kind, end) {
+            hold.duration = (end - start).max(0.0);
+        }
+    }
+}
+
+/// Returns the indices of notes further than `tolerance_ms` from the 1/`division` grid
+pub fn find_unsnapped(beatmap: &Beatmap, division: u32, tolerance_ms: f64) -> Vec<usize> {
+    beatmap.hit_objects.iter()
+        .enumerate()
+        .filter(|(_, hit_object)| {
+            let snapped = snap_time(beatmap, hit_object.start_time, division);
+            (hit_object.start_time - snapped).abs() > tolerance_ms
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Beat divisions tried by `dominant_snap`, from the coarsest
+pub const SNAP_DIVISIONS: [u32; 8] = [1, 2, 3, 4, 6, 8, 12, 16];
+/// Share of notes a division must cover to be the dominant snap
+pub const SNAP_COVERAGE: f64 = 0.9;
+/// Distance to the grid under which a note counts as snapped
+pub const SNAP_TOLERANCE_MS: f64 = 2.0;
+
+/// Coarsest division of `SNAP_DIVISIONS` most notes are snapped to
+///
+/// A division is dominant once `SNAP_COVERAGE` of the notes lie within
+/// `SNAP_TOLERANCE_MS` of its grid, so a 1/4 map with a few 1/6 fills
+/// still reports 4. Returns `None` for maps without notes or without enough
+/// snapped notes.
+pub fn dominant_snap(beatmap: &Beatmap) -> Option<u32> {
+    let total = beatmap.hit_objects.len();
+    if total == 0 {
+        return None;
+    }
+
+    SNAP_DIVISIONS.into_iter().find(|&division| {
+        let unsnapped = find_unsnapped(beatmap, division, SNAP_TOLERANCE_MS).len();
+        (total - unsnapped) as f64 >= SNAP_COVERAGE * total as f64
+    })
+}
+
+#[cfg(all(test, feature = "mania"))]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::{DifficultyPoint, EffectPoint, TimingPoint};
+    use crate::mania::new_note;
+
+    /// 120 BPM red line at 0 with green lines in the middle of the map
+    fn map_with_green_lines(times: &[f64]) -> Beatmap {
+        let mut map = Beatmap::default();
+        map.control_points.timing_points.push(TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+        map.control_points.difficulty_points.push(DifficultyPoint {
+            time: 1000.0,
+            slider_velocity: 2.0,
+            generate_ticks: true,
+        });
+        map.control_points.effect_points.push(EffectPoint {
+            time: 1000.0,
+            kiai: true,
+            scroll_speed: 3.0,
+        });
+        map.hit_objects = times.iter().map(|&time| new_note(time, 0, 4)).collect();
+        map
+    }
+
+    #[test]
+    fn green_lines_do_not_change_the_grid() {
+        let mut map = map_with_green_lines(&[1125.0, 1260.0, 1580.0]);
+
+        assert_eq!(find_unsnapped(&map, 4, 1.0), vec![1, 2]);
+
+        quantize(&mut map, 4);
+        let times: Vec<f64> = map.hit_objects.iter().map(|h| h.start_time).collect();
+        assert_eq!(times, vec![1125.0, 1250.0, 1625.0]);
+        assert!(find_unsnapped(&map, 4, 1.0).is_empty());
+    }
+}
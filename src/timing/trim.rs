@@ -0,0 +1,72 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+/// Removes everything after the end of the audio
+///
+/// Notes starting at or after `audio_length_ms` are removed and holds that
+/// straddle it are cut short. Control points, breaks and bookmarks after the
+/// end are dropped too, except the first red line so the map keeps a BPM.
+pub fn trim_to_length(beatmap: &mut Beatmap, audio_length_ms: f64) {
+    beatmap.hit_objects.retain(|hit_object| hit_object.start_time < audio_length_ms);
+
+    for hit_object in beatmap.hit_objects.iter_mut() {
+        if let HitObjectKind::Hold(ref mut hold) = hit_object.kind {
+            hold.duration = hold.duration.min(audio_length_ms - hit_object.start_time);
+        }
+    }
+
+    let control_points = &mut beatmap.control_points;
+    let first_timing = control_points.timing_points.first().map(|point| point.time);
+    control_points.timing_points.retain(|point| point.time <= audio_length_ms || Some(point.time) == first_timing);
+    control_points.effect_points.retain(|point| point.time <= audio_length_ms);
+    control_points.difficulty_points.retain(|point| point.time <= audio_length_ms);
+    control_points.sample_points.retain(|point| point.time <= audio_length_ms);
+
+    beatmap.breaks.retain(|break_period| break_period.start_time < audio_length_ms);
+    for break_period in beatmap.breaks.iter_mut() {
+        break_period.end_time = break_period.end_time.min(audio_length_ms);
+    }
+
+    beatmap.bookmarks.retain(|&bookmark| (bookmark as f64) <= audio_length_ms);
+}
+
+#[cfg(all(test, feature = "mania"))]
+mod tests {
+    use super::*;
+    use rosu_map::section::events::BreakPeriod;
+    use rosu_map::section::timing_points::TimingPoint;
+    use crate::mania::{end_time, new_hold, new_note};
+
+    #[test]
+    fn trims_notes_holds_and_breaks_past_the_audio() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.hit_objects = vec![
+            new_note(0.0, 0, 4),
+            new_hold(800.0, 500.0, 1, 4),
+            new_note(1000.0, 2, 4),
+            new_note(1500.0, 3, 4),
+        ];
+        map.control_points.timing_points = vec![
+            TimingPoint::new(2000.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+            TimingPoint::new(3000.0, 400.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE),
+        ];
+        map.breaks = vec![
+            BreakPeriod { start_time: 900.0, end_time: 1400.0 },
+            BreakPeriod { start_time: 1200.0, end_time: 1400.0 },
+        ];
+        map.bookmarks = vec![500, 1500];
+
+        trim_to_length(&mut map, 1000.0);
+
+        let objects: Vec<(f64, f64)> = map.hit_objects.iter()
+            .map(|hit_object| (hit_object.start_time, end_time(hit_object)))
+            .collect();
+        assert_eq!(objects, vec![(0.0, 0.0), (800.0, 1000.0)]);
+        assert_eq!(map.control_points.timing_points.len(), 1);
+        assert_eq!(map.breaks, vec![BreakPeriod { start_time: 900.0, end_time: 1000.0 }]);
+        assert_eq!(map.bookmarks, vec![500]);
+    }
+}
@@ -0,0 +1,120 @@
+use rosu_map::Beatmap;
+
+use crate::mania::column::key_count;
+use crate::mania::{end_time, new_note};
+use crate::rng::Rng;
+use crate::timing::beat_grid;
+
+/// Notes closer than this to a generated note keep it from being added
+const OCCUPIED_TOLERANCE_MS: f64 = 1.0;
+
+/// Pattern used to generate notes
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FillPattern {
+    /// Single notes rolling from the left column to the right one
+    Stream,
+    /// Alternating between the two center columns
+    Trill,
+    /// Repeating a single center column
+    Jack,
+}
+
+impl FillPattern {
+    /// Column of the `i`-th generated note
+    pub fn column(self, i: usize, key_count: u32) -> u32 {
+        let center = key_count / 2;
+
+        match self {
+            FillPattern::Stream => (i % key_count as usize) as u32,
+            FillPattern::Trill if key_count > 1 => center - 1 + (i % 2) as u32,
+            FillPattern::Trill | FillPattern::Jack => center.min(key_count - 1),
+        }
+    }
+}
+
+/// Fills `[start, end)` with notes on the 1/`division` grid
+///
+/// Grid lines that already have a note are skipped.
+pub fn fill_gap(beatmap: &mut Beatmap, start: f64, end: f64, division: u32, pattern: FillPattern) {
+    let keys = key_count(beatmap);
+
+    let times: Vec<f64> = beat_grid(beatmap, start, end, division)
+        .into_iter()
+        .filter(|&time| {
+            !beatmap.hit_objects.iter()
+                .any(|hit_object| (hit_object.start_time - time).abs() <= OCCUPIED_TOLERANCE_MS)
+        })
+        .collect();
+
+    for (i, time) in times.into_iter().enumerate() {
+        beatmap.hit_objects.push(new_note(time, pattern.column(i, keys), keys));
+    }
+
+    beatmap.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+}
+
+/// Appends an outro of random single notes whose density ramps down to zero
+///
+/// The outro starts on the first 1/4 grid line after the end of the map, under
+/// the last red line, and lasts `duration_ms`. The share of grid lines getting
+/// a note falls linearly from 1 to 0, spread evenly rather than drawn, so the
+/// density ramps down the same way for every seed. Only the columns are random,
+/// and consecutive notes never share one.
+pub fn add_outro(beatmap: &mut Beatmap, duration_ms: f64, seed: u64) {
+    let keys = key_count(beatmap);
+    let mut rng = Rng::new(seed);
+
+    let last_end = beatmap.hit_objects.iter().map(end_time).fold(0.0, f64::max);
+    let start = last_end + OCCUPIED_TOLERANCE_MS;
+    let mut previous = None;
+    let mut kept = 0.0;
+
+    for time in beat_grid(beatmap, start, start + duration_ms, 4) {
+        kept += 1.0 - (time - start) / duration_ms;
+        if kept < 1.0 {
+            continue;
+        }
+        kept -= 1.0;
+
+        let mut column = (rng.next_u64() % keys as u64) as u32;
+        if keys > 1 && previous == Some(column) {
+            column = (column + 1 + (rng.next_u64() % (keys as u64 - 1)) as u32) % keys;
+        }
+        previous = Some(column);
+
+        beatmap.hit_objects.push(new_note(time, column, keys));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+    use crate::mania::column::column;
+    use crate::mania::map_4k;
+
+    #[test]
+    fn outro_density_ramps_down() {
+        let mut map = map_4k(vec![new_note(0.0, 0, 4)]);
+        map.control_points.timing_points.push(TimingPoint::new(
+            0.0,
+            500.0,
+            false,
+            TimingPoint::DEFAULT_TIME_SIGNATURE,
+        ));
+
+        for seed in 0..16 {
+            let mut map = map.clone();
+            add_outro(&mut map, 8000.0, seed);
+
+            let outro = &map.hit_objects[1..];
+            assert!(outro.iter().all(|note| note.start_time > 0.0 && note.start_time < 8001.0));
+            assert!(outro.windows(2).all(|pair| column(&pair[0], 4) != column(&pair[1], 4)));
+
+            let quarters: Vec<usize> = (0..4)
+                .map(|q| outro.iter().filter(|note| (note.start_time / 2000.0) as usize == q).count())
+                .collect();
+            assert!(quarters.windows(2).all(|pair| pair[0] > pair[1]), "seed {}: {:?}", seed, quarters);
+        }
+    }
+}
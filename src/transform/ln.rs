@@ -0,0 +1,244 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::{HitObjectCircle, HitObjectHold, HitObjectKind};
+use rosu_map::util::Pos;
+
+use crate::mania::column::{column, key_count};
+use crate::rng::Rng;
+
+/// Holds shorter than this after a transform become circles
+pub const MIN_HOLD_MS: f64 = 30.0;
+/// Minimum time between a hold release and the next note in its column
+pub const RELEASE_GAP_MS: f64 = 1.0;
+
+/// Multiplies the duration of every hold by `factor`
+///
+/// Holds never extend past `RELEASE_GAP_MS` before the next note of their
+/// column, and holds ending up shorter than `MIN_HOLD_MS` become circles.
+pub fn scale_hold_lengths(beatmap: &mut Beatmap, factor: f64) {
+    let limits = next_in_column(beatmap);
+
+    for (hit_object, limit) in beatmap.hit_objects.iter_mut().zip(limits) {
+        let HitObjectKind::Hold(hold) = hit_object.kind else {
+            continue;
+        };
+
+        let mut duration = hold.duration * factor;
+        if let Some(next) = limit {
+            duration = duration.min(next - RELEASE_GAP_MS - hit_object.start_time);
+        }
+
+        hit_object.kind = if duration < MIN_HOLD_MS {
+            circle_at(hold.pos_x)
+        } else {
+            HitObjectKind::Hold(HitObjectHold { duration, ..hold })
+        };
+    }
+}
+
+/// Start time of the next note in the same column, for each hit object
+fn next_in_column(beatmap: &Beatmap) -> Vec<Option<f64>> {
+    let keys = key_count(beatmap);
+    let mut order: Vec<usize> = (0..beatmap.hit_objects.len()).collect();
+    order.sort_by(|&a, &b| {
+        beatmap.hit_objects[a].start_time.partial_cmp(&beatmap.hit_objects[b].start_time).unwrap()
+    });
+
+    let mut next = vec![None; beatmap.hit_objects.len()];
+    let mut upcoming: Vec<Option<f64>> = vec![None; keys as usize];
+
+    for &i in order.iter().rev() {
+        let hit_object = &beatmap.hit_objects[i];
+        if let Some(column) = column(hit_object, keys) {
+            next[i] = upcoming[column as usize];
+            upcoming[column as usize] = Some(hit_object.start_time);
+        }
+    }
+
+    next
+}
+
+/// Circle kind at the given x position, used when a hold becomes too short
+pub(crate) fn circle_at(x: f32) -> HitObjectKind {
+    HitObjectKind::Circle(HitObjectCircle {
+        pos: Pos { x, y: 0.0 },
+        new_combo: false,
+        combo_offset: 0,
+    })
+}
+
+/// Merges back-to-back holds of a column into one longer hold
+///
+/// A hold absorbs the next note of its column when that note is a hold
+/// starting at most `max_gap_ms` after it ends. Chains of fragments become
+/// a single hold keeping the first fragment's hitsounds.
+pub fn coalesce_holds(beatmap: &mut Beatmap, max_gap_ms: f64) {
+    let keys = key_count(beatmap);
+    let mut columns: Vec<Vec<usize>> = vec![Vec::new(); keys as usize];
+
+    for (i, hit_object) in beatmap.hit_objects.iter().enumerate() {
+        if let Some(column) = column(hit_object, keys) {
+            columns[column as usize].push(i);
+        }
+    }
+
+    let mut removed = vec![false; beatmap.hit_objects.len()];
+
+    for mut indices in columns {
+        indices.sort_by(|&a, &b| {
+            beatmap.hit_objects[a].start_time.partial_cmp(&beatmap.hit_objects[b].start_time).unwrap()
+        });
+
+        let mut current: Option<usize> = None;
+        for index in indices {
+            let next = &beatmap.hit_objects[index];
+            let HitObjectKind::Hold(next_hold) = next.kind else {
+                current = None;
+                continue;
+            };
+            let next_end = next.start_time + next_hold.duration;
+            let next_start = next.start_time;
+
+            let merged = current.is_some_and(|head| {
+                let head = &mut beatmap.hit_objects[head];
+                let HitObjectKind::Hold(ref mut hold) = head.kind else {
+                    return false;
+                };
+                if next_start - (head.start_time + hold.duration) > max_gap_ms {
+                    return false;
+                }
+                hold.duration = next_end - head.start_time;
+                true
+            });
+
+            if merged {
+                removed[index] = true;
+            } else {
+                current = Some(index);
+            }
+        }
+    }
+
+    let mut removed = removed.into_iter();
+    beatmap.hit_objects.retain(|_| !removed.next().unwrap());
+}
+
+/// Converts circles to holds or holds to circles until the LN ratio is near `target`
+///
+/// The notes converted are picked at random (deterministic for a given `seed`).
+/// New holds last half the time to the next note of their column, at least
+/// `min_hold_ms` and leaving `RELEASE_GAP_MS` before that note; circles without
+/// room for such a hold (including the last note of each column) are never
+/// converted, so high targets may fall short.
+pub fn target_ln_ratio(beatmap: &mut Beatmap, target: f64, min_hold_ms: f64, seed: u64) {
+    let keys = key_count(beatmap);
+    let total = beatmap.hit_objects.iter().filter(|hit_object| column(hit_object, keys).is_some()).count();
+    let holds = beatmap.hit_objects.iter()
+        .filter(|hit_object| matches!(hit_object.kind, HitObjectKind::Hold(_)))
+        .count();
+    let wanted = (target.clamp(0.0, 1.0) * total as f64).round() as usize;
+
+    let mut rng = Rng::new(seed);
+
+    if wanted < holds {
+        let mut candidates: Vec<usize> = (0..beatmap.hit_objects.len())
+            .filter(|&i| matches!(beatmap.hit_objects[i].kind, HitObjectKind::Hold(_)))
+            .collect();
+        rng.shuffle(&mut candidates);
+
+        for i in candidates.into_iter().take(holds - wanted) {
+            let HitObjectKind::Hold(hold) = beatmap.hit_objects[i].kind else {
+                continue;
+            };
+            beatmap.hit_objects[i].kind = circle_at(hold.pos_x);
+        }
+    } else if wanted > holds {
+        let limits = next_in_column(beatmap);
+        let mut candidates: Vec<(usize, f64)> = beatmap.hit_objects.iter()
+            .zip(limits)
+            .enumerate()
+            .filter_map(|(i, (hit_object, next))| {
+                let HitObjectKind::Circle(_) = hit_object.kind else {
+                    return None;
+                };
+                let room = next? - RELEASE_GAP_MS - hit_object.start_time;
+                if room < min_hold_ms {
+                    return None;
+                }
+                Some((i, ((room + RELEASE_GAP_MS) / 2.0).clamp(min_hold_ms, room)))
+            })
+            .collect();
+        rng.shuffle(&mut candidates);
+
+        for (i, duration) in candidates.into_iter().take(wanted - holds) {
+            let HitObjectKind::Circle(circle) = beatmap.hit_objects[i].kind else {
+                continue;
+            };
+            beatmap.hit_objects[i].kind = HitObjectKind::Hold(HitObjectHold { pos_x: circle.pos.x, duration });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::hit_objects::HitObject;
+    use crate::mania::{end_time, map_4k, new_hold, new_note};
+
+    fn is_hold(hit_object: &HitObject) -> bool {
+        matches!(hit_object.kind, HitObjectKind::Hold(_))
+    }
+
+    #[test]
+    fn halving_holds_leaves_no_overlap() {
+        let mut map = map_4k(vec![
+            new_hold(0.0, 200.0, 0, 4),
+            new_hold(0.0, 40.0, 1, 4),
+            new_hold(0.0, 1000.0, 2, 4),
+            new_note(300.0, 2, 4),
+        ]);
+
+        scale_hold_lengths(&mut map, 0.5);
+
+        assert_eq!(end_time(&map.hit_objects[0]), 100.0);
+        assert!(!is_hold(&map.hit_objects[1]));
+        assert_eq!(column(&map.hit_objects[1], 4), Some(1));
+        assert_eq!(end_time(&map.hit_objects[2]), 300.0 - RELEASE_GAP_MS);
+    }
+
+    #[test]
+    fn three_fragments_become_one_hold() {
+        let mut map = map_4k(vec![
+            new_hold(0.0, 100.0, 0, 4),
+            new_hold(110.0, 100.0, 0, 4),
+            new_hold(220.0, 80.0, 0, 4),
+            new_hold(500.0, 100.0, 0, 4),
+            new_hold(110.0, 100.0, 1, 4),
+        ]);
+
+        coalesce_holds(&mut map, 20.0);
+
+        let holds: Vec<(f64, f64, Option<u32>)> = map.hit_objects.iter()
+            .map(|hit_object| (hit_object.start_time, end_time(hit_object), column(hit_object, 4)))
+            .collect();
+        assert_eq!(holds, vec![(0.0, 300.0, Some(0)), (500.0, 600.0, Some(0)), (110.0, 210.0, Some(1))]);
+    }
+
+    #[test]
+    fn ln_ratio_rises_to_the_target() {
+        let mut objects: Vec<HitObject> = (0..20).map(|i| new_note(i as f64 * 100.0, (i % 4) as u32, 4)).collect();
+        objects[0] = new_hold(0.0, 200.0, 0, 4);
+        objects[1] = new_hold(100.0, 200.0, 1, 4);
+        let mut map = map_4k(objects);
+
+        target_ln_ratio(&mut map, 0.4, MIN_HOLD_MS, 1);
+
+        assert_eq!(map.hit_objects.iter().filter(|hit_object| is_hold(hit_object)).count(), 8);
+        assert_eq!(map.hit_objects.len(), 20);
+
+        let mut by_column: Vec<Vec<(f64, f64)>> = vec![Vec::new(); 4];
+        for hit_object in &map.hit_objects {
+            by_column[column(hit_object, 4).unwrap() as usize].push((hit_object.start_time, end_time(hit_object)));
+        }
+        assert!(by_column.iter().all(|notes| notes.windows(2).all(|pair| pair[0].1 < pair[1].0)));
+    }
+}
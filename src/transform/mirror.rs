@@ -0,0 +1,210 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use crate::mania::column::{column, key_count, set_column};
+use crate::mania::end_time;
+use crate::rng::Rng;
+
+/// Mirrors the columns (column `c` becomes `keys - 1 - c`)
+///
+/// Mirroring twice gives back the original columns.
+pub fn mirror_columns(map: &mut Beatmap) {
+    let keys = key_count(map);
+
+    for hit_object in map.hit_objects.iter_mut() {
+        if let Some(c) = column(hit_object, keys) {
+            set_column(hit_object, keys - 1 - c, keys);
+        }
+    }
+
+    map.version = format!("{} Mirror", map.version);
+}
+
+/// Mirrors the columns of hold notes, leaving circles in place
+///
+/// A hold is left in its column if its mirrored position would overlap another
+/// note (a circle, or a hold in its final position) in the target column.
+///
+/// # Returns
+/// Indices of the holds that were left in place because of a collision
+pub fn mirror_holds_only(map: &mut Beatmap) -> Vec<usize> {
+    let keys = key_count(map);
+
+    let columns: Vec<Option<u32>> = map.hit_objects.iter().map(|hit_object| column(hit_object, keys)).collect();
+    let mut moved: Vec<bool> = map.hit_objects.iter()
+        .zip(&columns)
+        .map(|(hit_object, c)| matches!(hit_object.kind, HitObjectKind::Hold(_)) && c.is_some())
+        .collect();
+    let final_column = |moved: &[bool], i: usize| columns[i].map(|c| if moved[i] { keys - 1 - c } else { c });
+
+    // Leaving a hold in place can make it collide with a moved one, so repeat
+    // until no moved hold collides
+    loop {
+        let mut by_column: Vec<Vec<usize>> = vec![Vec::new(); keys as usize];
+        for i in 0..map.hit_objects.len() {
+            if let Some(c) = final_column(&moved, i) {
+                by_column[c as usize].push(i);
+            }
+        }
+
+        let mut colliding = Vec::new();
+
+        for notes in by_column.iter_mut() {
+            notes.sort_by(|&a, &b| map.hit_objects[a].start_time.partial_cmp(&map.hit_objects[b].start_time).unwrap());
+
+            // Note with the latest end so far, which any overlap must include
+            let mut longest: Option<usize> = None;
+
+            for &i in notes.iter() {
+                if let Some(j) = longest
+                    && map.hit_objects[i].start_time <= end_time(&map.hit_objects[j])
+                {
+                    colliding.extend([i, j].into_iter().filter(|&k| moved[k]));
+                }
+
+                if longest.is_none_or(|j| end_time(&map.hit_objects[i]) > end_time(&map.hit_objects[j])) {
+                    longest = Some(i);
+                }
+            }
+        }
+
+        if colliding.is_empty() {
+            break;
+        }
+
+        for i in colliding {
+            moved[i] = false;
+        }
+    }
+
+    let mut skipped = Vec::new();
+
+    for (i, hit_object) in map.hit_objects.iter_mut().enumerate() {
+        if moved[i] {
+            set_column(hit_object, keys - 1 - columns[i].unwrap(), keys);
+        } else if matches!(hit_object.kind, HitObjectKind::Hold(_)) {
+            skipped.push(i);
+        }
+    }
+
+    map.version = format!("{} Hold Mirror", map.version);
+    skipped
+}
+
+/// Shifts every note `offset` columns to the right, wrapping around
+///
+/// Rotating by the key count leaves the columns untouched.
+pub fn rotate_columns(map: &mut Beatmap, offset: i32) {
+    let keys = key_count(map);
+
+    for hit_object in map.hit_objects.iter_mut() {
+        if let Some(c) = column(hit_object, keys) {
+            let rotated = (c as i64 + offset as i64).rem_euclid(keys as i64) as u32;
+            set_column(hit_object, rotated, keys);
+        }
+    }
+
+    map.version = format!("{} Rotate {}", map.version, offset);
+}
+
+/// Mirrors each beatmap with the given probability
+///
+/// The choice is deterministic for a given `seed`, so a marathon can be rebuilt
+/// identically.
+pub fn apply_random_mirrors(beatmaps: &mut [Beatmap], probability: f64, seed: u64) {
+    let mut rng = Rng::new(seed);
+
+    for beatmap in beatmaps.iter_mut() {
+        if rng.next_f64() < probability {
+            mirror_columns(beatmap);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::column::x_of;
+    use crate::mania::{new_hold, new_note};
+
+    fn map_with_columns(keys: u32) -> Beatmap {
+        let mut map = Beatmap {
+            circle_size: keys as f32,
+            ..Default::default()
+        };
+        for c in 0..keys {
+            map.hit_objects.push(new_note(c as f64 * 100.0, c, keys));
+            map.hit_objects.push(new_hold(c as f64 * 100.0 + 50.0, 20.0, c, keys));
+        }
+        map
+    }
+
+    fn positions(map: &Beatmap) -> Vec<Option<f32>> {
+        map.hit_objects.iter().map(x_of).collect()
+    }
+
+    #[test]
+    fn mirroring_twice_is_identity() {
+        for keys in [4, 7] {
+            let mut map = map_with_columns(keys);
+            let original = positions(&map);
+
+            mirror_columns(&mut map);
+            assert_ne!(positions(&map), original);
+            mirror_columns(&mut map);
+            assert_eq!(positions(&map), original);
+        }
+    }
+
+    #[test]
+    fn rotating_by_key_count_is_identity() {
+        for keys in [4, 7] {
+            let mut map = map_with_columns(keys);
+            let original = positions(&map);
+
+            rotate_columns(&mut map, keys as i32);
+            assert_eq!(positions(&map), original);
+            rotate_columns(&mut map, -1);
+            rotate_columns(&mut map, 1);
+            assert_eq!(positions(&map), original);
+        }
+    }
+
+    #[test]
+    fn random_mirrors_at_the_extreme_probabilities() {
+        let original = map_with_columns(4);
+        let mirrored = {
+            let mut map = original.clone();
+            mirror_columns(&mut map);
+            map
+        };
+
+        let mut always = vec![original.clone(); 5];
+        apply_random_mirrors(&mut always, 1.0, 42);
+        assert!(always.iter().all(|map| positions(map) == positions(&mirrored)));
+
+        let mut never = vec![original.clone(); 5];
+        apply_random_mirrors(&mut never, 0.0, 42);
+        assert!(never.iter().all(|map| *map == original));
+    }
+
+    #[test]
+    fn only_holds_move_and_colliding_ones_stay() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        map.hit_objects = vec![
+            new_hold(0.0, 500.0, 0, 4),
+            new_note(100.0, 1, 4),
+            new_hold(1000.0, 500.0, 1, 4),
+            new_note(1200.0, 2, 4),
+        ];
+
+        let skipped = mirror_holds_only(&mut map);
+
+        let columns: Vec<Option<u32>> = map.hit_objects.iter().map(|hit_object| column(hit_object, 4)).collect();
+        assert_eq!(columns, vec![Some(3), Some(1), Some(1), Some(2)]);
+        assert_eq!(skipped, vec![2]);
+    }
+}
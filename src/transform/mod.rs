@@ -0,0 +1,13 @@
+pub mod fill;
+pub mod ln;
+pub mod mirror;
+pub mod random;
+pub mod swing;
+pub mod thin;
+
+pub use fill::{FillPattern, add_outro, fill_gap};
+pub use ln::{coalesce_holds, scale_hold_lengths, target_ln_ratio};
+pub use mirror::{mirror_columns, mirror_holds_only, rotate_columns};
+pub use random::{alternate_columns, rebalance_columns};
+pub use swing::apply_swing;
+pub use thin::{cap_chord_size, spread_cluster, to_single_stream};
@@ -0,0 +1,218 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::HitObjectKind;
+
+use crate::mania::column::{column, column_histogram, key_count, set_column};
+use crate::mania::end_time;
+use crate::rng::Rng;
+
+/// Minimum number of chords sharing one column permutation in `rebalance_columns`
+pub const REBALANCE_SEGMENT_CHORDS: usize = 16;
+
+/// Evens out column usage by remapping columns section by section
+///
+/// The map is cut into sections of at least `REBALANCE_SEGMENT_CHORDS` chords,
+/// only where no hold is active. Each section gets its own column permutation
+/// sending its busiest columns to the columns used least so far, so patterns
+/// within a section keep their shape and chords stay chords. `seed` breaks
+/// ties between equally used columns. Maps that are already balanced are left untouched.
+pub fn rebalance_columns(beatmap: &mut Beatmap, seed: u64) {
+    let histogram = column_histogram(beatmap);
+    let (min, max) = (histogram.iter().min(), histogram.iter().max());
+    if max.zip(min).is_none_or(|(max, min)| max - min <= 1) {
+        return;
+    }
+
+    let keys = histogram.len();
+    let mut rng = Rng::new(seed);
+
+    let mut notes: Vec<(usize, f64, f64, usize)> = beatmap.hit_objects.iter()
+        .enumerate()
+        .filter_map(|(i, hit_object)| {
+            let column = column(hit_object, keys as u32)?;
+            Some((i, hit_object.start_time, end_time(hit_object), column as usize))
+        })
+        .collect();
+    notes.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let mut sections: Vec<&[(usize, f64, f64, usize)]> = Vec::new();
+    let mut section_start = 0;
+    let mut chords = 0;
+    let mut held_until = f64::NEG_INFINITY;
+
+    for i in 0..notes.len() {
+        let new_chord = i == 0 || notes[i].1 > notes[i - 1].1;
+        if new_chord {
+            if chords >= REBALANCE_SEGMENT_CHORDS && held_until < notes[i].1 {
+                sections.push(&notes[section_start..i]);
+                section_start = i;
+                chords = 0;
+            }
+            chords += 1;
+        }
+        held_until = held_until.max(notes[i].2);
+    }
+    sections.push(&notes[section_start..]);
+
+    let mut used = vec![0usize; keys];
+    let mut targets: Vec<(usize, usize)> = Vec::with_capacity(notes.len());
+
+    for section in sections {
+        let mut counts = vec![0usize; keys];
+        for &(_, _, _, column) in section {
+            counts[column] += 1;
+        }
+
+        let mut busiest = shuffled(keys, &mut rng);
+        busiest.sort_by_key(|&c| std::cmp::Reverse(counts[c]));
+        let mut emptiest = shuffled(keys, &mut rng);
+        emptiest.sort_by_key(|&c| used[c]);
+
+        let mut permutation = vec![0; keys];
+        for (&from, &to) in busiest.iter().zip(&emptiest) {
+            permutation[from] = to;
+            used[to] += counts[from];
+        }
+
+        targets.extend(section.iter().map(|&(index, _, _, column)| (index, permutation[column])));
+    }
+
+    for (index, target) in targets {
+        set_column(&mut beatmap.hit_objects[index], target as u32, keys as u32);
+    }
+}
+
+/// Columns `0..keys` in a seeded random order
+fn shuffled(keys: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut columns: Vec<usize> = (0..keys).collect();
+    rng.shuffle(&mut columns);
+    columns
+}
+
+/// Turns jacks into trills
+///
+/// Every run of consecutive single circles in one column (a jack, with no other
+/// note in between) alternates between its column and the next one (the
+/// previous one for the last column). A note stays put if the other column is
+/// held at that time.
+pub fn alternate_columns(beatmap: &mut Beatmap) {
+    let keys = key_count(beatmap);
+    if keys < 2 {
+        return;
+    }
+
+    let mut order: Vec<usize> = (0..beatmap.hit_objects.len())
+        .filter(|&i| column(&beatmap.hit_objects[i], keys).is_some())
+        .collect();
+    order.sort_by(|&a, &b| {
+        beatmap.hit_objects[a].start_time.partial_cmp(&beatmap.hit_objects[b].start_time).unwrap()
+    });
+
+    let holds: Vec<(u32, f64, f64)> = beatmap.hit_objects.iter()
+        .filter(|hit_object| matches!(hit_object.kind, HitObjectKind::Hold(_)))
+        .filter_map(|hit_object| {
+            Some((column(hit_object, keys)?, hit_object.start_time, end_time(hit_object)))
+        })
+        .collect();
+    let held = |c: u32, time: f64| holds.iter().any(|&(h, start, end)| h == c && start <= time && time <= end);
+
+    let is_single = |k: usize| {
+        let time = beatmap.hit_objects[order[k]].start_time;
+        let alone = (k == 0 || beatmap.hit_objects[order[k - 1]].start_time != time)
+            && (k + 1 == order.len() || beatmap.hit_objects[order[k + 1]].start_time != time);
+        alone && matches!(beatmap.hit_objects[order[k]].kind, HitObjectKind::Circle(_))
+    };
+
+    let mut moves: Vec<(usize, u32)> = Vec::new();
+    let mut k = 0;
+
+    while k < order.len() {
+        let Some(c) = column(&beatmap.hit_objects[order[k]], keys).filter(|_| is_single(k)) else {
+            k += 1;
+            continue;
+        };
+
+        let mut end = k + 1;
+        while end < order.len()
+            && is_single(end)
+            && column(&beatmap.hit_objects[order[end]], keys) == Some(c)
+        {
+            end += 1;
+        }
+
+        let other = if c + 1 < keys { c + 1 } else { c - 1 };
+        for j in (k + 1..end).step_by(2) {
+            let index = order[j];
+            if !held(other, beatmap.hit_objects[index].start_time) {
+                moves.push((index, other));
+            }
+        }
+
+        k = end;
+    }
+
+    for (index, target) in moves {
+        set_column(&mut beatmap.hit_objects[index], target, keys);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::new_note;
+
+    fn variance(histogram: &[usize]) -> f64 {
+        let mean = histogram.iter().sum::<usize>() as f64 / histogram.len() as f64;
+        histogram.iter().map(|&count| (count as f64 - mean).powi(2)).sum::<f64>() / histogram.len() as f64
+    }
+
+    /// Number of distinct columns at each timestamp
+    fn chord_sizes(map: &Beatmap) -> Vec<(i64, usize)> {
+        let mut chords: Vec<(i64, Vec<u32>)> = Vec::new();
+        for hit_object in &map.hit_objects {
+            let time = hit_object.start_time as i64;
+            let column = column(hit_object, 4).unwrap();
+            match chords.iter_mut().find(|(t, _)| *t == time) {
+                Some((_, columns)) if !columns.contains(&column) => columns.push(column),
+                Some(_) => {}
+                None => chords.push((time, vec![column])),
+            }
+        }
+        chords.sort();
+        chords.into_iter().map(|(time, columns)| (time, columns.len())).collect()
+    }
+
+    #[test]
+    fn rebalance_lowers_variance_and_keeps_chords() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            ..Default::default()
+        };
+        for i in 0..48 {
+            let time = i as f64 * 100.0;
+            map.hit_objects.push(new_note(time, 0, 4));
+            if i % 4 == 0 {
+                map.hit_objects.push(new_note(time, 1, 4));
+            }
+        }
+        let before = chord_sizes(&map);
+
+        rebalance_columns(&mut map, 7);
+
+        assert!(variance(&column_histogram(&map)) < variance(&[48, 12, 0, 0]));
+        assert_eq!(chord_sizes(&map), before);
+    }
+
+    #[test]
+    fn four_note_jack_becomes_a_trill() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            hit_objects: (0..4).map(|i| new_note(i as f64 * 100.0, 1, 4)).collect(),
+            ..Default::default()
+        };
+
+        alternate_columns(&mut map);
+
+        let columns: Vec<Option<u32>> = map.hit_objects.iter().map(|note| column(note, 4)).collect();
+        assert_eq!(columns, vec![Some(1), Some(2), Some(1), Some(2)]);
+    }
+}
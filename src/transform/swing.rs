@@ -0,0 +1,61 @@
+use rosu_map::Beatmap;
+
+use crate::timing::timing_point_at;
+
+/// How close to the half beat a note must be to count as off-beat
+const OFF_BEAT_TOLERANCE_MS: f64 = 2.0;
+
+/// Swings the off-beat 1/2 notes
+///
+/// Notes on the half beat of the governing red line move to `ratio` of the beat
+/// (0.5 leaves them in place, 0.66 gives a triplet swing). On-beat notes and
+/// every other subdivision stay put; holds move as a whole.
+pub fn apply_swing(beatmap: &mut Beatmap, ratio: f64) {
+    let shifts: Vec<f64> = beatmap.hit_objects.iter()
+        .map(|hit_object| {
+            let Some(point) = timing_point_at(beatmap, hit_object.start_time) else {
+                return 0.0;
+            };
+            let beat_start = point.time
+                + ((hit_object.start_time - point.time) / point.beat_len).floor() * point.beat_len;
+            let half = beat_start + point.beat_len / 2.0;
+
+            if (hit_object.start_time - half).abs() <= OFF_BEAT_TOLERANCE_MS {
+                beat_start + ratio * point.beat_len - hit_object.start_time
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
+    for (hit_object, shift) in beatmap.hit_objects.iter_mut().zip(shifts) {
+        hit_object.start_time += shift;
+    }
+
+    beatmap.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosu_map::section::timing_points::TimingPoint;
+    use crate::mania::new_note;
+
+    #[test]
+    fn only_off_beat_notes_move() {
+        let mut map = Beatmap {
+            circle_size: 4.0,
+            hit_objects: [0.0, 250.0, 500.0, 625.0, 750.0]
+                .into_iter()
+                .map(|time| new_note(time, 0, 4))
+                .collect(),
+            ..Default::default()
+        };
+        map.control_points.timing_points.push(TimingPoint::new(0.0, 500.0, false, TimingPoint::DEFAULT_TIME_SIGNATURE));
+
+        apply_swing(&mut map, 0.66);
+
+        let times: Vec<f64> = map.hit_objects.iter().map(|h| h.start_time).collect();
+        assert_eq!(times, vec![0.0, 330.0, 500.0, 625.0, 830.0]);
+    }
+}
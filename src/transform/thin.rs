@@ -0,0 +1,194 @@
+use rosu_map::Beatmap;
+use rosu_map::section::hit_objects::{HitObjectHold, HitObjectKind};
+
+use super::ln::{MIN_HOLD_MS, RELEASE_GAP_MS, circle_at};
+use crate::analysis::chords::group_chords;
+use crate::analysis::patterns::CHORD_TOLERANCE_MS;
+use crate::mania::column::{column, hand_of, key_count};
+
+/// Order in which columns are kept when thinning chords: outer columns first,
+/// alternating hands (0, k-1, 1, k-2, ...)
+fn column_priority(key_count: u32) -> Vec<u32> {
+    (0..key_count)
+        .map(|i| if i % 2 == 0 { i / 2 } else { key_count - 1 - i / 2 })
+        .collect()
+}
+
+/// Removes notes from every chord larger than `max_size`
+///
+/// Chords always keep the same columns by priority (outer columns first,
+/// alternating hands), so the thinned patterns stay consistent.
+pub fn cap_chord_size(beatmap: &mut Beatmap, max_size: usize, tolerance_ms: f64) {
+    let priority = column_priority(key_count(beatmap));
+    let mut removed: Vec<usize> = Vec::new();
+
+    for chord in group_chords(beatmap, tolerance_ms) {
+        if chord.size() <= max_size {
+            continue;
+        }
+
+        let mut notes: Vec<(usize, u32)> = chord.indices.iter()
+            .copied()
+            .zip(chord.columns.iter().copied())
+            .collect();
+        notes.sort_by_key(|&(_, column)| priority.iter().position(|&c| c == column));
+        removed.extend(notes[max_size..].iter().map(|&(index, _)| index));
+    }
+
+    remove_indices(beatmap, &removed);
+}
+
+/// Removes the hit objects at the given indices, keeping the others in order
+fn remove_indices(beatmap: &mut Beatmap, indices: &[usize]) {
+    let mut keep = vec![true; beatmap.hit_objects.len()];
+    for &index in indices {
+        keep[index] = false;
+    }
+
+    let mut keep = keep.into_iter();
+    beatmap.hit_objects.retain(|_| keep.next().unwrap());
+}
+
+/// Reduces the map to a pure stream of single notes
+///
+/// Each chord keeps one note, preferably on the same hand as the previous note
+/// but in another column, so the stream keeps flowing without jacks. Holds
+/// still sounding when the next note starts are shortened (see
+/// `scale_hold_lengths` for the limits), so at most one note sounds at a time.
+pub fn to_single_stream(beatmap: &mut Beatmap) {
+    let keys = key_count(beatmap);
+    let priority = column_priority(keys);
+    let mut removed: Vec<usize> = Vec::new();
+    let mut kept: Vec<usize> = Vec::new();
+    let mut previous: Option<u32> = None;
+
+    for chord in group_chords(beatmap, CHORD_TOLERANCE_MS) {
+        let notes: Vec<(usize, u32)> = chord.indices.iter()
+            .copied()
+            .zip(chord.columns.iter().copied())
+            .collect();
+
+        let score = |&(_, column): &(usize, u32)| {
+            let same_hand = previous.is_some_and(|p| hand_of(p, keys) == hand_of(column, keys));
+            let jack = previous == Some(column);
+            let rank = priority.iter().position(|&c| c == column).unwrap_or(0);
+            (!same_hand || jack, jack, rank)
+        };
+        let &(index, column) = notes.iter().min_by_key(|note| score(note)).unwrap();
+
+        removed.extend(notes.iter().map(|&(i, _)| i).filter(|&i| i != index));
+        kept.push(index);
+        previous = Some(column);
+    }
+
+    for pair in kept.windows(2) {
+        let next_start = beatmap.hit_objects[pair[1]].start_time;
+        let hit_object = &mut beatmap.hit_objects[pair[0]];
+
+        if let HitObjectKind::Hold(hold) = hit_object.kind {
+            let duration = hold.duration.min(next_start - RELEASE_GAP_MS - hit_object.start_time);
+            hit_object.kind = if duration < MIN_HOLD_MS {
+                circle_at(hold.pos_x)
+            } else {
+                HitObjectKind::Hold(HitObjectHold { duration, ..hold })
+            };
+        }
+    }
+
+    remove_indices(beatmap, &removed);
+}
+
+/// Spreads the notes starting in `[start, end)` so they are at least `spacing_ms` apart
+///
+/// Notes are taken in time order (then column order within a chord), keep
+/// their columns and hold durations, and are only ever pushed later. Notes
+/// pushed past `end` are not checked against the notes that follow.
+pub fn spread_cluster(beatmap: &mut Beatmap, start: f64, end: f64, spacing_ms: f64) {
+    let keys = key_count(beatmap);
+
+    let mut notes: Vec<(usize, f64, u32)> = beatmap.hit_objects.iter()
+        .enumerate()
+        .filter(|(_, hit_object)| hit_object.start_time >= start && hit_object.start_time < end)
+        .filter_map(|(i, hit_object)| column(hit_object, keys).map(|c| (i, hit_object.start_time, c)))
+        .collect();
+    notes.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.2.cmp(&b.2)));
+
+    let mut previous: Option<f64> = None;
+    for (i, time, _) in notes {
+        let time = previous.map_or(time, |previous| time.max(previous + spacing_ms));
+        beatmap.hit_objects[i].start_time = time;
+        previous = Some(time);
+    }
+
+    beatmap.hit_objects.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mania::{end_time, map_4k, new_hold, new_note};
+
+    /// `(time, column)` of every note, in order
+    fn notes(map: &Beatmap) -> Vec<(f64, u32)> {
+        map.hit_objects.iter()
+            .map(|hit_object| (hit_object.start_time, column(hit_object, 4).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn quads_become_jumps_on_the_same_columns() {
+        let mut map = map_4k(Vec::new());
+        for i in 0..3 {
+            for column in [2, 0, 3, 1] {
+                map.hit_objects.push(new_note(i as f64 * 500.0, column, 4));
+            }
+        }
+
+        cap_chord_size(&mut map, 2, CHORD_TOLERANCE_MS);
+
+        let mut kept = notes(&map);
+        kept.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(kept, vec![(0.0, 0), (0.0, 3), (500.0, 0), (500.0, 3), (1000.0, 0), (1000.0, 3)]);
+    }
+
+    #[test]
+    fn single_stream_has_one_note_at_a_time() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects = vec![
+            new_note(0.0, 0, 4),
+            new_note(0.0, 2, 4),
+            new_hold(100.0, 500.0, 1, 4),
+            new_note(100.0, 3, 4),
+            new_note(200.0, 0, 4),
+            new_note(200.0, 1, 4),
+            new_note(200.0, 2, 4),
+            new_note(300.0, 3, 4),
+        ];
+
+        to_single_stream(&mut map);
+
+        let mut objects: Vec<(f64, f64)> = map.hit_objects.iter()
+            .map(|hit_object| (hit_object.start_time, end_time(hit_object)))
+            .collect();
+        objects.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(objects.len(), 4);
+        assert!(objects.windows(2).all(|pair| pair[0].1 < pair[1].0));
+    }
+
+    #[test]
+    fn spread_cluster_spaces_notes_apart() {
+        let mut map = map_4k(Vec::new());
+        map.hit_objects = vec![
+            new_note(0.0, 0, 4),
+            new_note(100.0, 1, 4),
+            new_note(100.0, 0, 4),
+            new_note(110.0, 2, 4),
+            new_note(120.0, 3, 4),
+            new_note(500.0, 1, 4),
+        ];
+
+        spread_cluster(&mut map, 100.0, 200.0, 30.0);
+
+        assert_eq!(notes(&map), vec![(0.0, 0), (100.0, 0), (130.0, 1), (160.0, 2), (190.0, 3), (500.0, 1)]);
+    }
+}